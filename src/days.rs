@@ -1,4 +1,16 @@
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day19;
+pub mod day20;
+pub mod day22;
+pub mod day24;
 pub mod day25;
+pub mod day5;
+pub mod day6;
+pub mod day7;
 
 pub use day25 as day;
 
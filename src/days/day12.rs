@@ -1,94 +1,190 @@
 use crate::harness::input::RawInput;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 pub fn solve_part1(input: RawInput) -> usize {
-    count_routes(input, false)
+    count_routes(input, &SingleVisit)
 }
 
 pub fn solve_part2(input: RawInput) -> usize {
-    count_routes(input, true)
+    count_routes(input, &OneDoubleVisit)
 }
 
+/// Decides whether a route may enter a small cave. Routes may always pass
+/// through big caves.
+trait VisitPolicy {
+    /// `visits` holds the number of times the route has entered each small
+    /// cave so far, indexed by id, counting `start` as entered once.
+    fn can_enter(&self, caves: &CaveSystem, cave: CaveId, visits: &[u8]) -> bool;
+}
+
+/// Each small cave may be visited at most once.
+struct SingleVisit;
+
+impl VisitPolicy for SingleVisit {
+    fn can_enter(&self, caves: &CaveSystem, cave: CaveId, visits: &[u8]) -> bool {
+        cave != caves.start && visits[cave] == 0
+    }
+}
+
+/// A single small cave other than `start` may be visited twice per route.
+struct OneDoubleVisit;
+
+impl VisitPolicy for OneDoubleVisit {
+    fn can_enter(&self, caves: &CaveSystem, cave: CaveId, visits: &[u8]) -> bool {
+        cave != caves.start && (visits[cave] == 0 || visits.iter().all(|&count| count < 2))
+    }
+}
+
+type CaveId = usize;
+
+/// Caves interned to dense ids, so that visit counts can be kept in a `Vec`.
 #[derive(Debug)]
-enum PendingStep<'a> {
-    Explore(&'a str),
-    Backtrack {
-        room: &'a str,
-        is_double_visit: bool,
-    },
+struct CaveSystem {
+    names: Vec<String>,
+    is_small: Vec<bool>,
+    neighbors: Vec<Vec<CaveId>>,
+    start: CaveId,
+    end: CaveId,
 }
 
-fn count_routes(input: RawInput, can_double_visit: bool) -> usize {
-    let paths = parse_paths(&input);
-    let mut seen = HashSet::<&str>::new();
-    let mut has_double_visited = false;
-    let mut pending = vec![PendingStep::Explore("start")];
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct RouteState {
+    cave: CaveId,
+    /// Entries for big caves stay at zero, so that passing through them
+    /// does not split states.
+    visits: Vec<u8>,
+}
+
+fn count_routes(input: RawInput, policy: &impl VisitPolicy) -> usize {
+    let caves = CaveSystem::parse(input);
+    let mut visits = vec![0; caves.names.len()];
+    visits[caves.start] = 1;
+    let initial_state = RouteState {
+        cave: caves.start,
+        visits,
+    };
+    count_routes_from(&caves, policy, initial_state, &mut HashMap::new())
+}
+
+fn count_routes_from(
+    caves: &CaveSystem,
+    policy: &impl VisitPolicy,
+    state: RouteState,
+    memo: &mut HashMap<RouteState, usize>,
+) -> usize {
+    if state.cave == caves.end {
+        return 1;
+    }
+    if let Some(&count) = memo.get(&state) {
+        return count;
+    }
     let mut count = 0;
-    while let Some(step) = pending.pop() {
-        match step {
-            PendingStep::Explore(room) => {
-                if room == "end" {
-                    count += 1;
-                    continue;
-                }
-                if is_small(room) {
-                    let is_double_visit = seen.contains(room);
-                    if is_double_visit {
-                        has_double_visited = true;
-                    } else {
-                        seen.insert(room);
-                    }
-                    pending.push(PendingStep::Backtrack {
-                        room,
-                        is_double_visit,
-                    });
-                }
-                pending.extend(
-                    paths
-                        .get(room)
-                        .unwrap_or(&vec![])
-                        .iter()
-                        .filter(|&next_room| {
-                            (can_double_visit && !has_double_visited && *next_room != "start")
-                                || !seen.contains(next_room)
-                        })
-                        .map(|&next_room| PendingStep::Explore(next_room)),
-                );
-            }
-            PendingStep::Backtrack {
-                room,
-                is_double_visit,
-            } => {
-                if is_double_visit {
-                    has_double_visited = false;
-                } else {
-                    seen.remove(room);
-                }
+    for &next_cave in &caves.neighbors[state.cave] {
+        let mut visits = state.visits.clone();
+        if caves.is_small[next_cave] {
+            if !policy.can_enter(caves, next_cave, &state.visits) {
+                continue;
             }
+            visits[next_cave] += 1;
         }
+        let next_state = RouteState {
+            cave: next_cave,
+            visits,
+        };
+        count += count_routes_from(caves, policy, next_state, memo);
     }
+    memo.insert(state, count);
     count
 }
 
-fn parse_paths<'a>(input: &'a RawInput) -> HashMap<&'a str, Vec<&'a str>> {
-    let paths = input
-        .as_str()
-        .lines()
-        .map(|line| line.split('-').collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-    let mut result = HashMap::<&'a str, Vec<&'a str>>::new();
-    let mut insert_path = |a: &'a str, b: &'a str| {
-        if a != "end" {
-            result.entry(a).or_default().push(b);
+impl CaveSystem {
+    fn parse(input: RawInput) -> Self {
+        let mut ids = HashMap::<&str, CaveId>::new();
+        let mut names = Vec::<String>::new();
+        let mut neighbors = Vec::<Vec<CaveId>>::new();
+        let mut intern = |name| {
+            *ids.entry(name).or_insert_with(|| {
+                names.push(name.to_owned());
+                neighbors.push(vec![]);
+                neighbors.len() - 1
+            })
+        };
+        let edges = input
+            .as_str()
+            .lines()
+            .map(|line| {
+                let (a, b) = line.split_once('-').unwrap();
+                (intern(a), intern(b))
+            })
+            .collect::<Vec<_>>();
+        let start = intern("start");
+        let end = intern("end");
+        for (a, b) in edges {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
         }
-    };
-    for endpoints in paths {
-        insert_path(&endpoints[0], &endpoints[1]);
-        insert_path(&endpoints[1], &endpoints[0]);
+        let is_small = names.iter().map(|name| is_small(name)).collect();
+        Self {
+            names,
+            is_small,
+            neighbors,
+            start,
+            end,
+        }
+    }
+
+    fn name(&self, cave: CaveId) -> &str {
+        &self.names[cave]
     }
-    result
 }
 
 fn is_small(room: &str) -> bool {
-    room.bytes().all(|b| (b'a'..=b'z').contains(&b))
+    room.bytes().all(|b| b.is_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL_EXAMPLE: &str = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end";
+    const LARGER_EXAMPLE: &str =
+        "dc-end\nHN-start\nstart-kj\ndc-start\ndc-HN\nLN-dc\nHN-end\nkj-sa\nkj-HN\nkj-dc";
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(solve_part1(RawInput::new(SMALL_EXAMPLE)), 10);
+        assert_eq!(solve_part2(RawInput::new(SMALL_EXAMPLE)), 36);
+        assert_eq!(solve_part1(RawInput::new(LARGER_EXAMPLE)), 19);
+        assert_eq!(solve_part2(RawInput::new(LARGER_EXAMPLE)), 103);
+    }
+
+    #[test]
+    fn test_visit_policies() {
+        // The only choice is whether to go back through A to b a second time.
+        let text = "start-A\nA-b\nb-end";
+        assert_eq!(count_routes(RawInput::new(text), &SingleVisit), 1);
+        assert_eq!(count_routes(RawInput::new(text), &OneDoubleVisit), 2);
+        // start may never be revisited, even as the double visit.
+        let text = "start-b\nb-end";
+        assert_eq!(count_routes(RawInput::new(text), &OneDoubleVisit), 1);
+    }
+
+    /// Allows each small cave, including `start`, as many visits as its limit,
+    /// or one if it has none.
+    struct PerCaveLimits(HashMap<&'static str, u8>);
+
+    impl VisitPolicy for PerCaveLimits {
+        fn can_enter(&self, caves: &CaveSystem, cave: CaveId, visits: &[u8]) -> bool {
+            visits[cave] < self.0.get(caves.name(cave)).copied().unwrap_or(1)
+        }
+    }
+
+    #[test]
+    fn test_custom_policy() {
+        let text = "start-A\nA-b\nb-end";
+        let policy = PerCaveLimits([("b", 3)].into_iter().collect());
+        assert_eq!(count_routes(RawInput::new(text), &policy), 3);
+        let policy = PerCaveLimits([("b", 3), ("start", 2)].into_iter().collect());
+        assert_eq!(count_routes(RawInput::new(text), &policy), 9);
+    }
 }
@@ -1,18 +1,19 @@
 use crate::harness::input::{LineInput, RawInput};
 use crate::regex;
+use crate::util::coords::Coord2;
 use crate::util::re;
-use std::collections::HashSet;
+use crate::util::render::Rendering;
+use crate::util::sparse_grid::SparseGrid;
 
 pub fn solve_part1(input: RawInput) -> usize {
-    let Input { mut dots, folds } = parse_input(input);
-    apply_fold(&mut dots, folds[0]);
-    dots.len()
+    let Input { dots, folds } = parse_input(input);
+    apply_fold(&dots, folds[0]).len()
 }
 
 pub fn solve_part2(input: RawInput) -> String {
     let Input { mut dots, folds } = parse_input(input);
     for fold in folds {
-        apply_fold(&mut dots, fold);
+        dots = apply_fold(&dots, fold);
     }
     print_dots(&dots);
     "".to_owned()
@@ -20,13 +21,13 @@ pub fn solve_part2(input: RawInput) -> String {
 
 #[derive(Debug)]
 struct Input {
-    dots: HashSet<(u32, u32)>,
+    dots: SparseGrid<bool>,
     folds: Vec<Fold>,
 }
 
 #[derive(Copy, Clone, Debug)]
 struct Fold {
-    value: u32,
+    value: i64,
     is_x: bool,
 }
 
@@ -34,13 +35,13 @@ fn parse_input(input: RawInput) -> Input {
     let groups = input.grouped_lines(|line| line.single::<String>());
     let dots = groups[0]
         .iter()
-        .map(|line| LineInput::new(line).split(","))
-        .map(|parts| (parts[0], parts[1]))
+        .map(|line| LineInput::new(line).split::<i64>(","))
+        .map(|parts| (Coord2(parts[1], parts[0]), true))
         .collect();
     let folds = groups[1]
         .iter()
         .map(|line| {
-            re::parse_with_regex::<(char, u32)>(regex!(r"^fold along (.)=(\d+)$"), line).unwrap()
+            re::parse_with_regex::<(char, i64)>(regex!(r"^fold along (.)=(\d+)$"), line).unwrap()
         })
         .map(|(c, value)| Fold {
             value,
@@ -50,45 +51,58 @@ fn parse_input(input: RawInput) -> Input {
     Input { dots, folds }
 }
 
-fn apply_fold(dots: &mut HashSet<(u32, u32)>, Fold { value, is_x }: Fold) {
-    if is_x {
-        fold_x(dots, value);
-    } else {
-        fold_y(dots, value);
-    }
+/// Reflects the dots beyond the fold line onto the near side. Dots on the
+/// line itself disappear.
+fn apply_fold(dots: &SparseGrid<bool>, Fold { value, is_x }: Fold) -> SparseGrid<bool> {
+    let reflect = |x: i64| if x > value { 2 * value - x } else { x };
+    dots.iter()
+        .filter(|&(Coord2(i, j), _)| if is_x { j != value } else { i != value })
+        .map(|(Coord2(i, j), &is_dot)| {
+            let point = if is_x {
+                Coord2(i, reflect(j))
+            } else {
+                Coord2(reflect(i), j)
+            };
+            (point, is_dot)
+        })
+        .collect()
 }
 
-fn fold_x(dots: &mut HashSet<(u32, u32)>, x: u32) {
-    let folded_dots = dots
-        .iter()
-        .filter(|&dot| dot.0 > x)
-        .map(|&dot| dot)
-        .collect::<Vec<_>>();
-    for dot in folded_dots {
-        dots.remove(&dot);
-        dots.insert((2 * x - dot.0, dot.1));
-    }
+fn print_dots(dots: &SparseGrid<bool>) {
+    println!("{}", render_dots(dots));
 }
 
-fn fold_y(dots: &mut HashSet<(u32, u32)>, y: u32) {
-    let folded_dots = dots
-        .iter()
-        .filter(|&dot| dot.1 > y)
-        .map(|&dot| dot)
-        .collect::<Vec<_>>();
-    for dot in folded_dots {
-        dots.remove(&dot);
-        dots.insert((dot.0, 2 * y - dot.1));
-    }
+/// Renders the bounding box of the dots, leaving out blank margins.
+fn render_dots(dots: &SparseGrid<bool>) -> Rendering {
+    dots.render(|&is_dot| if is_dot { '█' } else { ' ' })
 }
 
-fn print_dots(dots: &HashSet<(u32, u32)>) {
-    let max_x = dots.iter().map(|&(x, _)| x).max().unwrap();
-    let max_y = dots.iter().map(|&(_, y)| y).max().unwrap();
-    for y in 0..=max_y {
-        let s = (0..=max_x)
-            .map(|x| if dots.contains(&(x, y)) { '█' } else { ' ' })
-            .collect::<String>();
-        println!("{}", s);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let input = RawInput::from_test_input(include_str!("../../input/day13-test-input.txt"));
+        assert_eq!(solve_part1(input), 17);
+        let Input { mut dots, folds } = parse_input(input);
+        for fold in folds {
+            dots = apply_fold(&dots, fold);
+        }
+        assert_eq!(
+            render_dots(&dots).to_string(),
+            ["█████", "█   █", "█   █", "█   █", "█████"].join("\n")
+        );
+    }
+
+    #[test]
+    fn test_fold_beyond_dots() {
+        let input = RawInput::new("0,0\n1,1\n\nfold along y=5\nfold along x=4");
+        assert_eq!(solve_part1(input), 2);
+        let Input { mut dots, folds } = parse_input(input);
+        for fold in folds {
+            dots = apply_fold(&dots, fold);
+        }
+        assert_eq!(render_dots(&dots).to_string(), "█ \n █");
     }
 }
@@ -1,11 +1,15 @@
 use crate::harness::input::RawInput;
+use crate::util::bigint::BigUint;
+use crate::util::matrix::{self, Ordinary};
+use crate::util::nums::Arithmetic;
+use ndarray::{Array1, Array2};
 use std::collections::HashMap;
 
-pub fn solve_part1(input: RawInput) -> u64 {
+pub fn solve_part1(input: RawInput) -> BigUint {
     solve(input, 10)
 }
 
-pub fn solve_part2(input: RawInput) -> u64 {
+pub fn solve_part2(input: RawInput) -> BigUint {
     solve(input, 40)
 }
 
@@ -17,12 +21,25 @@ struct Input {
     rules: PairMap<char>,
 }
 
-fn solve(input: RawInput, n_steps: usize) -> u64 {
+fn solve<T: Arithmetic + Ord>(input: RawInput, n_steps: usize) -> T {
     let Input { template, rules } = parse_input(input);
-    let mut pair_counts = count_pairs(&template);
-    for _ in 0..n_steps {
-        pair_counts = apply_step(&pair_counts, &rules);
-    }
+    let pairs = rules.keys().copied().collect::<Vec<_>>();
+    let initial_counts = count_pairs(&template);
+    let initial_vector = Array1::from_shape_fn(pairs.len(), |i| {
+        initial_counts.get(&pairs[i]).cloned().unwrap_or_default()
+    });
+    let transform = step_transform(&pairs, &rules);
+    // Squaring costs a matrix product per doubling, which only beats
+    // stepping the vector once there are more steps than pairs.
+    let final_vector = if n_steps < pairs.len() {
+        (0..n_steps).fold(initial_vector, |vector, _| {
+            matrix::multiply_vector(&Ordinary, &transform, &vector)
+        })
+    } else {
+        let transform = matrix::pow(&Ordinary, &transform, n_steps as u64);
+        matrix::multiply_vector(&Ordinary, &transform, &initial_vector)
+    };
+    let pair_counts = pairs.into_iter().zip(final_vector).collect();
     score_final_counts(&pair_counts)
 }
 
@@ -39,34 +56,59 @@ fn parse_input(input: RawInput) -> Input {
     Input { template, rules }
 }
 
-fn count_pairs(chars: &[char]) -> PairMap<u64> {
+fn count_pairs<T: Arithmetic>(chars: &[char]) -> PairMap<T> {
     let mut result = HashMap::new();
     for i in 0..chars.len() - 1 {
-        *result.entry((chars[i], chars[i + 1])).or_default() += 1;
+        *result.entry((chars[i], chars[i + 1])).or_default() += 1.into();
     }
     result
 }
 
-fn apply_step(pair_counts: &PairMap<u64>, rules: &PairMap<char>) -> PairMap<u64> {
-    let mut result = HashMap::new();
-    for (&pair, &count) in pair_counts {
+/// The matrix taking the count of each pair, indexed as in `pairs`, to the
+/// counts after one insertion step.
+fn step_transform<T: Arithmetic>(pairs: &[(char, char)], rules: &PairMap<char>) -> Array2<T> {
+    let indices = pairs
+        .iter()
+        .enumerate()
+        .map(|(i, &pair)| (pair, i))
+        .collect::<PairMap<usize>>();
+    let mut result = Array2::default((pairs.len(), pairs.len()));
+    for (i, &pair) in pairs.iter().enumerate() {
         let (first, second) = pair;
         let inserted = rules[&pair];
-        *result.entry((first, inserted)).or_default() += count;
-        *result.entry((inserted, second)).or_default() += count;
+        result[[indices[&(first, inserted)], i]] += 1.into();
+        result[[indices[&(inserted, second)], i]] += 1.into();
     }
     result
 }
 
-fn score_final_counts(pair_counts: &PairMap<u64>) -> u64 {
-    let mut char_counts = HashMap::<char, u64>::new();
-    for (&(first, second), &pair_count) in pair_counts {
-        *char_counts.entry(first).or_default() += pair_count;
-        *char_counts.entry(second).or_default() += pair_count;
+fn score_final_counts<T: Arithmetic + Ord>(pair_counts: &PairMap<T>) -> T {
+    let mut char_counts = HashMap::<char, T>::new();
+    for (&(first, second), pair_count) in pair_counts {
+        *char_counts.entry(first).or_default() += pair_count.clone();
+        *char_counts.entry(second).or_default() += pair_count.clone();
     }
     // Counting characters appearing in pairs double-counts all characters
     // except for the first and last, so divide by 2 and round up.
-    let min = (*char_counts.values().min().unwrap() + 1) / 2;
-    let max = (*char_counts.values().max().unwrap() + 1) / 2;
+    let round_up_half = |count: T| (count + 1.into()) / 2.into();
+    let min = round_up_half(char_counts.values().min().unwrap().clone());
+    let max = round_up_half(char_counts.values().max().unwrap().clone());
     max - min
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_big_counts_match_primitive() {
+        let text = include_str!("../../input/day14-test-input.txt");
+        let input = RawInput::from_test_input(text);
+        for n_steps in [10, 40, 100] {
+            assert_eq!(
+                solve::<BigUint>(input, n_steps).to_string(),
+                solve::<u128>(input, n_steps).to_string()
+            );
+        }
+    }
+}
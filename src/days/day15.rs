@@ -1,44 +1,75 @@
 use crate::harness::input::RawInput;
+use crate::util::coords::Coord2;
 use crate::util::grid::Grid;
+use crate::util::render::Rendering;
 use crate::util::search::dijkstra;
-use ndarray::Array2;
 
 pub fn solve_part1(input: RawInput) -> usize {
     let grid = Grid::parse_digits(input.as_str());
-    shortest_path_length(&grid)
+    solve(&grid)
 }
 
 pub fn solve_part2(input: RawInput) -> usize {
     let grid = Grid::parse_digits(input.as_str());
     let grid = expand_grid(&grid);
-    shortest_path_length(&grid)
+    solve(&grid)
 }
 
-fn shortest_path_length(grid: &Grid<u32>) -> usize {
-    dijkstra::search(
+fn solve(grid: &Grid<u32>) -> usize {
+    shortest_path(grid).0
+}
+
+fn shortest_path(grid: &Grid<u32>) -> (usize, Vec<[usize; 2]>) {
+    let result = dijkstra::search(
         [0, 0],
         |&ij| {
             grid.orthogonal_neighbors(ij)
                 .map(|neighbor| (neighbor, grid[neighbor] as usize))
         },
         |&[i, j]| i == grid.nrows() - 1 && j == grid.ncols() - 1,
-    )
-    .goal_state()
-    .unwrap()
-    .distance
+    );
+    let length = result.goal_state().unwrap().distance;
+    let path = result
+        .path_to_goal()
+        .unwrap()
+        .into_iter()
+        .copied()
+        .collect();
+    (length, path)
+}
+
+fn render_path(grid: &Grid<u32>, path: &[[usize; 2]]) -> Rendering {
+    grid.render(|&risk| risk)
+        .highlight(path.iter().map(|&[i, j]| Coord2(i as i64, j as i64)))
 }
 
 fn expand_grid(grid: &Grid<u32>) -> Grid<u32> {
-    let mut result: Array2<u32> = Array2::zeros((grid.nrows() * 5, grid.ncols() * 5));
-    for i in 0..5 {
-        for j in 0..5 {
-            for ii in 0..grid.nrows() {
-                for jj in 0..grid.ncols() {
-                    result[[i * grid.nrows() + ii, j * grid.ncols() + jj]] =
-                        (grid[[ii, jj]] + i as u32 + j as u32 - 1) % 9 + 1
-                }
-            }
-        }
+    grid.tile(5, 5, |&risk, [i, j]| {
+        (risk + i as u32 + j as u32 - 1) % 9 + 1
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        let grid = Grid::parse_digits("116\n911\n991");
+        let (length, path) = shortest_path(&grid);
+        assert_eq!(length, 4);
+        assert_eq!(path, vec![[0, 0], [0, 1], [1, 1], [1, 2], [2, 2]]);
+        let highlighted = |c| format!("\x1b[7m{}\x1b[0m", c);
+        assert_eq!(
+            render_path(&grid, &path).to_string(),
+            format!(
+                "{}{}6\n9{}{}\n99{}",
+                highlighted(1),
+                highlighted(1),
+                highlighted(1),
+                highlighted(1),
+                highlighted(1)
+            )
+        );
     }
-    Grid(result)
 }
@@ -1,26 +1,81 @@
 use crate::harness::input::RawInput;
 use hex::FromHex;
-use std::iter;
+use std::fmt::{self, Display, Formatter};
+use std::{cmp, iter};
 
 pub fn solve_part1(input: RawInput) -> u32 {
     let bytes = input.single_line(|line| Vec::from_hex(line.as_str()).unwrap());
     let mut parser = Parser::new(&bytes);
-    parser.read_packet();
+    parser.read_transmission().unwrap();
     parser.version_sum
 }
 
 pub fn solve_part2(input: RawInput) -> u64 {
     let bytes = input.single_line(|line| Vec::from_hex(line.as_str()).unwrap());
-    let packet = Parser::new(&bytes).read_packet();
+    let packet = Parser::new(&bytes).read_transmission().unwrap();
     packet.value()
 }
 
-#[derive(Debug)]
-enum Packet {
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Packet {
+    pub version: u32,
+    pub contents: Contents,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Contents {
     Literal(u64),
     Operator(u32, Vec<Packet>),
 }
 
+/// How an operator packet gives the extent of its subpackets: as a 15-bit
+/// total length in bits, or as an 11-bit number of subpackets.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LengthType {
+    Bits,
+    Count,
+}
+
+/// Why a transmission could not be decoded, and the bit offset at which the
+/// problem was found.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct DecodeError {
+    pub offset: usize,
+    pub kind: DecodeErrorKind,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DecodeErrorKind {
+    /// The stream ended in the middle of a packet.
+    Truncated,
+    /// A literal's value does not fit in a `u64`.
+    LiteralOverflow,
+    /// An operator has the wrong number of subpackets: comparisons need
+    /// exactly two, and the others at least one.
+    InvalidArity { op_type: u32, count: usize },
+    /// Subpackets ran past the length in bits given by their operator.
+    LengthMismatch,
+    /// The padding after the outermost packet contains a set bit.
+    NonZeroPadding,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            DecodeErrorKind::Truncated => write!(f, "Stream ends early")?,
+            DecodeErrorKind::LiteralOverflow => write!(f, "Literal overflows 64 bits")?,
+            DecodeErrorKind::InvalidArity { op_type, count } => {
+                write!(f, "Operator of type {} has {} subpackets", op_type, count)?
+            }
+            DecodeErrorKind::LengthMismatch => write!(f, "Subpackets overrun their length")?,
+            DecodeErrorKind::NonZeroPadding => write!(f, "Padding has a set bit")?,
+        }
+        write!(f, " at bit {}.", self.offset)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
 #[derive(Debug)]
 struct Parser<'a> {
     bytes: &'a [u8],
@@ -37,65 +92,110 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn read_packet(&mut self) -> Packet {
-        self.version_sum += self.read_number(3);
-        let packet_type = self.read_number(3);
-        match packet_type {
-            4 => self.read_literal(),
-            _ => Packet::Operator(packet_type, self.read_subpackets()),
+    /// Reads the outermost packet and checks that only zero padding follows.
+    fn read_transmission(&mut self) -> Result<Packet, DecodeError> {
+        let packet = self.read_packet()?;
+        while self.pos < 8 * self.bytes.len() {
+            if self.read_bit()? {
+                return Err(self.error_at(self.pos - 1, DecodeErrorKind::NonZeroPadding));
+            }
         }
+        Ok(packet)
     }
 
-    fn read_literal(&mut self) -> Packet {
-        let mut result = 0;
-        while self.read_bit() {
-            result = 16 * result + self.read_number(4) as u64;
+    fn read_packet(&mut self) -> Result<Packet, DecodeError> {
+        let start = self.pos;
+        let version = self.read_number(3)?;
+        self.version_sum += version;
+        let packet_type = self.read_number(3)?;
+        let contents = match packet_type {
+            4 => self.read_literal()?,
+            _ => {
+                let subpackets = self.read_subpackets()?;
+                let count = subpackets.len();
+                let valid = match packet_type {
+                    5..=7 => count == 2,
+                    _ => count >= 1,
+                };
+                if !valid {
+                    let kind = DecodeErrorKind::InvalidArity {
+                        op_type: packet_type,
+                        count,
+                    };
+                    return Err(self.error_at(start, kind));
+                }
+                Contents::Operator(packet_type, subpackets)
+            }
+        };
+        Ok(Packet { version, contents })
+    }
+
+    fn read_literal(&mut self) -> Result<Contents, DecodeError> {
+        let mut result: u64 = 0;
+        loop {
+            let group_start = self.pos;
+            let more = self.read_bit()?;
+            if result >> 60 != 0 {
+                return Err(self.error_at(group_start, DecodeErrorKind::LiteralOverflow));
+            }
+            result = 16 * result + self.read_number(4)? as u64;
+            if !more {
+                return Ok(Contents::Literal(result));
+            }
         }
-        result = 16 * result + self.read_number(4) as u64;
-        Packet::Literal(result)
     }
 
-    fn read_subpackets(&mut self) -> Vec<Packet> {
-        if self.read_bit() {
+    fn read_subpackets(&mut self) -> Result<Vec<Packet>, DecodeError> {
+        if self.read_bit()? {
             self.read_counted_subpackets()
         } else {
             self.read_bit_counted_subpackets()
         }
     }
 
-    fn read_bit_counted_subpackets(&mut self) -> Vec<Packet> {
-        let nbits = self.read_number(15);
+    fn read_bit_counted_subpackets(&mut self) -> Result<Vec<Packet>, DecodeError> {
+        let nbits = self.read_number(15)?;
         let end = self.pos + nbits as usize;
         let mut subpackets = vec![];
         while self.pos < end {
-            subpackets.push(self.read_packet());
+            subpackets.push(self.read_packet()?);
         }
-        subpackets
+        if self.pos > end {
+            return Err(self.error_at(end, DecodeErrorKind::LengthMismatch));
+        }
+        Ok(subpackets)
     }
 
-    fn read_counted_subpackets(&mut self) -> Vec<Packet> {
-        let nsubpackets = self.read_number(11) as usize;
+    fn read_counted_subpackets(&mut self) -> Result<Vec<Packet>, DecodeError> {
+        let nsubpackets = self.read_number(11)? as usize;
         iter::repeat_with(|| self.read_packet())
             .take(nsubpackets)
             .collect()
     }
 
-    fn read_number(&mut self, nbits: usize) -> u32 {
-        (0..nbits).fold(0, |acc, _| 2 * acc + self.read_bit() as u32)
+    fn read_number(&mut self, nbits: usize) -> Result<u32, DecodeError> {
+        (0..nbits).try_fold(0, |acc, _| Ok(2 * acc + self.read_bit()? as u32))
     }
 
-    fn read_bit(&mut self) -> bool {
+    fn read_bit(&mut self) -> Result<bool, DecodeError> {
+        if self.pos >= 8 * self.bytes.len() {
+            return Err(self.error_at(self.pos, DecodeErrorKind::Truncated));
+        }
         let bit = get_bit(self.bytes, self.pos);
         self.pos += 1;
-        bit
+        Ok(bit)
+    }
+
+    fn error_at(&self, offset: usize, kind: DecodeErrorKind) -> DecodeError {
+        DecodeError { offset, kind }
     }
 }
 
 impl Packet {
     fn value(&self) -> u64 {
-        match self {
-            Self::Literal(value) => *value,
-            Self::Operator(op_type, subpackets) => {
+        match &self.contents {
+            Contents::Literal(value) => *value,
+            Contents::Operator(op_type, subpackets) => {
                 let mut subvalues = subpackets.iter().map(|subpacket| subpacket.value());
                 match *op_type {
                     0 => subvalues.sum(),
@@ -118,6 +218,300 @@ impl Packet {
     }
 }
 
+/// Writes packets as a BITS bit stream, the inverse of `Parser`.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    bits: Vec<bool>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes `packet`, calling `choose_length_type` on each operator packet.
+    /// Panics if a packet has a version or type which does not fit in three
+    /// bits, an operator type of 4, or does not fit in the chosen length type.
+    pub fn write_packet(
+        &mut self,
+        packet: &Packet,
+        choose_length_type: &mut impl FnMut(&Packet) -> LengthType,
+    ) {
+        assert!(
+            packet.version < 8,
+            "Version {} does not fit.",
+            packet.version
+        );
+        self.write_number(packet.version.into(), 3);
+        match &packet.contents {
+            Contents::Literal(value) => {
+                self.write_number(4, 3);
+                self.write_literal(*value);
+            }
+            Contents::Operator(op_type, subpackets) => {
+                assert!(
+                    *op_type < 8 && *op_type != 4,
+                    "Invalid operator type {}.",
+                    op_type
+                );
+                self.write_number((*op_type).into(), 3);
+                match choose_length_type(packet) {
+                    LengthType::Bits => {
+                        self.write_bit(false);
+                        let length_pos = self.bits.len();
+                        self.write_number(0, 15);
+                        for subpacket in subpackets {
+                            self.write_packet(subpacket, choose_length_type);
+                        }
+                        let nbits = self.bits.len() - length_pos - 15;
+                        assert!(nbits < 1 << 15, "{} bits of subpackets do not fit.", nbits);
+                        let mut length = Encoder::new();
+                        length.write_number(nbits as u64, 15);
+                        self.bits[length_pos..length_pos + 15].copy_from_slice(&length.bits);
+                    }
+                    LengthType::Count => {
+                        assert!(
+                            subpackets.len() < 1 << 11,
+                            "{} subpackets do not fit.",
+                            subpackets.len()
+                        );
+                        self.write_bit(true);
+                        self.write_number(subpackets.len() as u64, 11);
+                        for subpacket in subpackets {
+                            self.write_packet(subpacket, choose_length_type);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes the value in as few groups of four bits as possible.
+    fn write_literal(&mut self, value: u64) {
+        let ngroups = cmp::max(1, (64 - value.leading_zeros() as usize).div_ceil(4));
+        for i in (0..ngroups).rev() {
+            self.write_bit(i > 0);
+            self.write_number(value >> (4 * i), 4);
+        }
+    }
+
+    /// Writes the low `nbits` bits of `value`, most significant first.
+    fn write_number(&mut self, value: u64, nbits: usize) {
+        for i in (0..nbits).rev() {
+            self.write_bit(value >> i & 1 == 1);
+        }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        self.bits.push(bit);
+    }
+
+    /// The bits written so far, padded with zeros to a whole number of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bits
+            .chunks(8)
+            .map(|chunk| (0..8).fold(0, |acc, i| 2 * acc + *chunk.get(i).unwrap_or(&false) as u8))
+            .collect()
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode_upper(self.to_bytes())
+    }
+}
+
+/// Encodes a packet as hex, using the shorter subpacket count for operators
+/// whenever it fits.
+pub fn encode_hex(packet: &Packet) -> String {
+    let mut encoder = Encoder::new();
+    encoder.write_packet(packet, &mut |packet| match &packet.contents {
+        Contents::Operator(_, subpackets) if subpackets.len() >= 1 << 11 => LengthType::Bits,
+        _ => LengthType::Count,
+    });
+    encoder.to_hex()
+}
+
 fn get_bit(bytes: &[u8], i: usize) -> bool {
     bytes[i / 8] & (1 << (7 - (i % 8))) > 0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::random::Random;
+
+    fn random_packet(random: &mut Random, depth: u32) -> Packet {
+        let version = random.below(8) as u32;
+        let op_type = random.below(8) as u32;
+        let contents = if depth == 0 || op_type == 4 {
+            // Vary the size so that literals take from one to 16 groups.
+            Contents::Literal(random.next_u64() >> random.below(64))
+        } else {
+            let nsubpackets = match op_type {
+                5..=7 => 2,
+                _ => 1 + random.below(4),
+            };
+            let subpackets = (0..nsubpackets)
+                .map(|_| random_packet(random, depth - 1))
+                .collect();
+            Contents::Operator(op_type, subpackets)
+        };
+        Packet { version, contents }
+    }
+
+    fn decode_hex(hex: &str) -> (Packet, u32) {
+        let bytes = Vec::from_hex(hex).unwrap();
+        let mut parser = Parser::new(&bytes);
+        let packet = parser.read_transmission().unwrap();
+        (packet, parser.version_sum)
+    }
+
+    fn version_sum(packet: &Packet) -> u32 {
+        packet.version
+            + match &packet.contents {
+                Contents::Literal(_) => 0,
+                Contents::Operator(_, subpackets) => subpackets.iter().map(version_sum).sum(),
+            }
+    }
+
+    #[test]
+    fn test_encode_examples() {
+        let literal = |version, value| Packet {
+            version,
+            contents: Contents::Literal(value),
+        };
+        let operator = |version, op_type, subpackets| Packet {
+            version,
+            contents: Contents::Operator(op_type, subpackets),
+        };
+        assert_eq!(encode_hex(&literal(6, 2021)), "D2FE28");
+        let mut encoder = Encoder::new();
+        let packet = operator(1, 6, vec![literal(6, 10), literal(2, 20)]);
+        encoder.write_packet(&packet, &mut |_| LengthType::Bits);
+        assert_eq!(encoder.to_hex(), "38006F45291200");
+        let packet = operator(7, 3, vec![literal(2, 1), literal(4, 2), literal(1, 3)]);
+        assert_eq!(encode_hex(&packet), "EE00D40C823060");
+        assert_eq!(decode_hex("EE00D40C823060").0, packet);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut random = Random::new(0x6a09_e667_f3bc_c908);
+        for _ in 0..500 {
+            let packet = random_packet(&mut random, 4);
+            let mut encoder = Encoder::new();
+            let mut lengths = Random::new(random.next_u64() | 1);
+            encoder.write_packet(&packet, &mut |_| {
+                if lengths.below(2) == 0 {
+                    LengthType::Bits
+                } else {
+                    LengthType::Count
+                }
+            });
+            let (decoded, decoded_version_sum) = decode_hex(&encoder.to_hex());
+            assert_eq!(decoded, packet);
+            assert_eq!(decoded_version_sum, version_sum(&packet));
+            assert_eq!(decode_hex(&encode_hex(&packet)).0, packet);
+        }
+    }
+
+    #[test]
+    fn test_wide_operator() {
+        let subpackets = vec![
+            Packet {
+                version: 0,
+                contents: Contents::Literal(1),
+            };
+            2500
+        ];
+        let packet = Packet {
+            version: 5,
+            contents: Contents::Operator(0, subpackets),
+        };
+        assert_eq!(decode_hex(&encode_hex(&packet)).0.value(), 2500);
+    }
+
+    #[test]
+    #[should_panic(expected = "Version 8 does not fit.")]
+    fn test_encode_wide_version() {
+        encode_hex(&Packet {
+            version: 8,
+            contents: Contents::Literal(1),
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid operator type 4.")]
+    fn test_encode_literal_type_operator() {
+        let literal = Packet {
+            version: 0,
+            contents: Contents::Literal(1),
+        };
+        encode_hex(&Packet {
+            version: 0,
+            contents: Contents::Operator(4, vec![literal]),
+        });
+    }
+
+    fn decode_error(hex: &str) -> DecodeError {
+        let bytes = Vec::from_hex(hex).unwrap();
+        Parser::new(&bytes).read_transmission().unwrap_err()
+    }
+
+    fn error(offset: usize, kind: DecodeErrorKind) -> DecodeError {
+        DecodeError { offset, kind }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode_error("D2FE"), error(16, DecodeErrorKind::Truncated));
+        assert_eq!(
+            decode_error("D2FE29"),
+            error(23, DecodeErrorKind::NonZeroPadding)
+        );
+        assert_eq!(decode_error(""), error(0, DecodeErrorKind::Truncated));
+
+        // Seventeen groups of ones, one more than fits in 64 bits.
+        let mut encoder = Encoder::new();
+        encoder.write_number(4, 6);
+        for _ in 0..16 {
+            encoder.write_number(0b11111, 5);
+        }
+        encoder.write_number(0b01111, 5);
+        let kind = DecodeErrorKind::LiteralOverflow;
+        assert_eq!(decode_error(&encoder.to_hex()), error(86, kind));
+
+        let literal = |value| Packet {
+            version: 0,
+            contents: Contents::Literal(value),
+        };
+        let operator = |op_type, subpackets| Packet {
+            version: 1,
+            contents: Contents::Operator(op_type, subpackets),
+        };
+        let comparison = operator(6, vec![literal(1), literal(2), literal(3)]);
+        let packet = operator(0, vec![literal(7), comparison]);
+        let kind = DecodeErrorKind::InvalidArity {
+            op_type: 6,
+            count: 3,
+        };
+        assert_eq!(decode_error(&encode_hex(&packet)), error(29, kind));
+        let kind = DecodeErrorKind::InvalidArity {
+            op_type: 2,
+            count: 0,
+        };
+        assert_eq!(
+            decode_error(&encode_hex(&operator(2, vec![]))),
+            error(0, kind)
+        );
+
+        // Version 0 and type 3 with a bit length of 10, one short of the
+        // literal inside.
+        let mut encoder = Encoder::new();
+        encoder.write_number(3, 6);
+        encoder.write_bit(false);
+        encoder.write_number(10, 15);
+        encoder.write_packet(&literal(5), &mut |_| LengthType::Count);
+        let kind = DecodeErrorKind::LengthMismatch;
+        assert_eq!(decode_error(&encoder.to_hex()), error(32, kind));
+    }
+}
@@ -1,16 +1,19 @@
 use crate::harness::input::{LineInput, RawInput};
-use crate::util::coords::Coord3;
+use crate::util::coords::{Coord3, Rotation};
 use std::cmp;
 use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
 
 pub fn solve_part1(input: RawInput) -> usize {
     let scanners = parse_scanners(input);
-    solve(&scanners).beacons.len()
+    solve_completely(&scanners).beacons.len()
 }
 
 pub fn solve_part2(input: RawInput) -> i32 {
     let scanners = parse_scanners(input);
-    let solved_scanners = solve(&scanners).scanners;
+    let solved_scanners = solve_completely(&scanners).scanners;
     let mut best_distance = 0;
     for scanner1 in &solved_scanners {
         for scanner2 in &solved_scanners {
@@ -42,180 +45,366 @@ fn parse_scanners(input: RawInput) -> Vec<Vec<Point>> {
         .collect()
 }
 
+/// The result of aligning as many scanners as possible.
 #[derive(Debug)]
-struct Solution {
+struct PartialSolution {
+    /// Groups of at least two scanners which could be aligned with each
+    /// other, each in the coordinates of its lowest-numbered scanner. The
+    /// component containing scanner 0, if any, comes first.
+    components: Vec<Solution>,
+    /// Scanners which share enough beacons with no other scanner.
+    unsolved: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub struct Solution {
     beacons: HashSet<Point>,
     scanners: Vec<SolvedScanner>,
 }
 
 #[derive(Debug)]
 struct SolvedScanner {
+    index: usize,
+    /// The scanner this one was aligned against, or `None` for the root of
+    /// its component.
+    parent: Option<usize>,
     location: Point,
-    orientation: Orientation,
+    orientation: Rotation,
     absolute_beacons: Vec<Point>,
 }
 
-fn solve(beacons_by_scanner: &[Vec<Point>]) -> Solution {
-    let displacements_by_beacon_by_scanner = beacons_by_scanner
-        .iter()
-        .map(|beacons| get_beacon_displacements(beacons))
-        .collect::<Vec<_>>();
-    let mut solved_scanners_by_index = HashMap::<usize, SolvedScanner>::new();
-    let mut unsolved_scanners_by_index = beacons_by_scanner
-        .iter()
-        .map(|beacons| beacons.clone())
-        .enumerate()
-        .collect::<HashMap<_, _>>();
-    let mut seen_pairs = HashSet::new();
-    solved_scanners_by_index.insert(
-        0,
-        SolvedScanner {
-            location: Point::default(),
-            orientation: Orientation::default(),
-            absolute_beacons: beacons_by_scanner[0].clone(),
-        },
-    );
-    while unsolved_scanners_by_index.len() > 0 {
-        let (i, solved_scanner, new_seen_pairs) = solve_next_scanner(
-            &displacements_by_beacon_by_scanner,
-            &solved_scanners_by_index,
-            &unsolved_scanners_by_index,
-            &seen_pairs,
+impl Solution {
+    /// Writes the beacon map as `beacons.ply` and `beacons.csv`, and the
+    /// alignment graph as `alignment.dot`, into `dir`.
+    pub fn export(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join("beacons.ply"), self.to_ply())?;
+        fs::write(dir.join("beacons.csv"), self.to_csv())?;
+        fs::write(dir.join("alignment.dot"), self.alignment_dot())
+    }
+
+    fn sorted_beacons(&self) -> Vec<Point> {
+        let mut beacons = self.beacons.iter().copied().collect::<Vec<_>>();
+        beacons.sort_unstable();
+        beacons
+    }
+
+    /// An ASCII PLY point cloud with beacons in white and scanners in red.
+    fn to_ply(&self) -> String {
+        let beacons = self.sorted_beacons();
+        let mut result = format!(
+            "ply\nformat ascii 1.0\nelement vertex {}\n",
+            beacons.len() + self.scanners.len()
         );
-        seen_pairs.extend(new_seen_pairs);
-        unsolved_scanners_by_index.remove(&i);
-        solved_scanners_by_index.insert(i, solved_scanner);
-    }
-    let beacons = solved_scanners_by_index
-        .values()
-        .flat_map(|scanner| scanner.absolute_beacons.iter().map(|&b| b))
-        .collect::<HashSet<Point>>();
-    let mut scanners = Vec::with_capacity(solved_scanners_by_index.len());
-    for i in 0..solved_scanners_by_index.len() {
-        scanners.push(solved_scanners_by_index.remove(&i).unwrap());
-    }
-    Solution { beacons, scanners }
+        for property in [
+            "int x",
+            "int y",
+            "int z",
+            "uchar red",
+            "uchar green",
+            "uchar blue",
+        ] {
+            result += &format!("property {}\n", property);
+        }
+        result += "end_header\n";
+        for Coord3(x, y, z) in beacons {
+            result += &format!("{} {} {} 255 255 255\n", x, y, z);
+        }
+        for scanner in &self.scanners {
+            let Coord3(x, y, z) = scanner.location;
+            result += &format!("{} {} {} 255 0 0\n", x, y, z);
+        }
+        result
+    }
+
+    /// One row per point, with the scanner number filled in for scanners.
+    fn to_csv(&self) -> String {
+        let mut result = String::from("kind,scanner,x,y,z\n");
+        for Coord3(x, y, z) in self.sorted_beacons() {
+            result += &format!("beacon,,{},{},{}\n", x, y, z);
+        }
+        for scanner in &self.scanners {
+            let Coord3(x, y, z) = scanner.location;
+            result += &format!("scanner,{},{},{},{}\n", scanner.index, x, y, z);
+        }
+        result
+    }
+
+    /// A Graphviz digraph with an edge from each scanner to the scanners
+    /// which were aligned against it.
+    fn alignment_dot(&self) -> String {
+        let mut result = String::from("digraph alignment {\n");
+        for scanner in &self.scanners {
+            let Coord3(x, y, z) = scanner.location;
+            result += &format!(
+                "    {} [label=\"{}\\n({},{},{})\"];\n",
+                scanner.index, scanner.index, x, y, z
+            );
+        }
+        for scanner in &self.scanners {
+            if let Some(parent) = scanner.parent {
+                result += &format!("    {} -> {};\n", parent, scanner.index);
+            }
+        }
+        result += "}\n";
+        result
+    }
 }
 
-fn solve_next_scanner(
-    displacements_by_beacon_by_scanner: &[Vec<HashSet<Point>>],
-    solved_scanners_by_index: &HashMap<usize, SolvedScanner>,
-    unsolved_scanners_by_index: &HashMap<usize, Vec<Point>>,
-    seen_pairs: &HashSet<(usize, usize)>,
-) -> (usize, SolvedScanner, Vec<(usize, usize)>) {
-    let mut new_seen_pairs = vec![];
-    for (&i, solved_scanner) in solved_scanners_by_index {
-        let solved_displacements = &displacements_by_beacon_by_scanner[i];
-        for (&j, unsolved_scanner) in unsolved_scanners_by_index {
-            if seen_pairs.contains(&(i, j)) {
-                continue;
+/// The number of beacons two scanners must share to be aligned in the puzzle.
+const MIN_SHARED_BEACONS: usize = 12;
+
+/// Rotation-invariant summary of the displacement between two beacons: the
+/// absolute values of its components, sorted.
+type Fingerprint = Point;
+
+/// Beacon pairs `[i, j, k, l]` whose fingerprints match, where `i` and `j`
+/// index the beacons of one scanner and `k` and `l` those of another.
+type PairMatches = Vec<[usize; 4]>;
+
+/// Aligns every scanner, panicking if they don't all form one component.
+fn solve_completely(beacons_by_scanner: &[Vec<Point>]) -> Solution {
+    let mut partial = solve(beacons_by_scanner, MIN_SHARED_BEACONS);
+    if partial.components.len() != 1 || !partial.unsolved.is_empty() {
+        panic!(
+            "Scanners don't form one component: {} components, unsolved {:?}.",
+            partial.components.len(),
+            partial.unsolved
+        );
+    }
+    partial.components.remove(0)
+}
+
+/// Aligns scanners which share at least `min_shared_beacons` beacons,
+/// growing a component from each scanner not yet reached by an earlier one.
+fn solve(beacons_by_scanner: &[Vec<Point>], min_shared_beacons: usize) -> PartialSolution {
+    let matches_by_scanner_pair =
+        find_candidate_scanner_pairs(beacons_by_scanner, min_shared_beacons);
+    let mut is_reached = vec![false; beacons_by_scanner.len()];
+    let mut components = vec![];
+    let mut unsolved = vec![];
+    for root in 0..beacons_by_scanner.len() {
+        if is_reached[root] {
+            continue;
+        }
+        is_reached[root] = true;
+        let mut scanners = vec![SolvedScanner {
+            index: root,
+            parent: None,
+            location: Point::default(),
+            orientation: Rotation::IDENTITY,
+            absolute_beacons: beacons_by_scanner[root].clone(),
+        }];
+        let mut next_pending = 0;
+        while next_pending < scanners.len() {
+            let solved_scanner = &scanners[next_pending];
+            next_pending += 1;
+            let mut newly_solved = vec![];
+            for (j, new_beacons) in beacons_by_scanner.iter().enumerate() {
+                if is_reached[j] {
+                    continue;
+                }
+                let matches = match matches_by_scanner_pair.get(&(solved_scanner.index, j)) {
+                    Some(matches) => matches,
+                    None => continue,
+                };
+                if let Some(new_solved_scanner) =
+                    align_scanner(solved_scanner, j, new_beacons, matches, min_shared_beacons)
+                {
+                    is_reached[j] = true;
+                    newly_solved.push(new_solved_scanner);
+                }
             }
-            new_seen_pairs.push((i, j));
-            let unsolved_displacements = &displacements_by_beacon_by_scanner[j];
-            if let Some(new_solved_scanner) = try_overlapping_scanner_pair(
-                solved_scanner,
-                solved_displacements,
-                unsolved_scanner,
-                unsolved_displacements,
-            ) {
-                return (j, new_solved_scanner, new_seen_pairs);
+            scanners.extend(newly_solved);
+        }
+        if scanners.len() == 1 {
+            unsolved.push(root);
+            continue;
+        }
+        let beacons = scanners
+            .iter()
+            .flat_map(|scanner| scanner.absolute_beacons.iter().copied())
+            .collect::<HashSet<Point>>();
+        components.push(Solution { beacons, scanners });
+    }
+    PartialSolution {
+        components,
+        unsolved,
+    }
+}
+
+/// Indexes every beacon pair by fingerprint, then returns the matching pairs
+/// for each ordered pair of scanners which share enough fingerprints to
+/// possibly overlap.
+fn find_candidate_scanner_pairs(
+    beacons_by_scanner: &[Vec<Point>],
+    min_shared_beacons: usize,
+) -> HashMap<(usize, usize), PairMatches> {
+    let mut pairs_by_fingerprint = HashMap::<Fingerprint, Vec<[usize; 3]>>::new();
+    for (scanner, beacons) in beacons_by_scanner.iter().enumerate() {
+        for i in 0..beacons.len() {
+            for j in i + 1..beacons.len() {
+                pairs_by_fingerprint
+                    .entry(fingerprint(beacons[j] - beacons[i]))
+                    .or_default()
+                    .push([scanner, i, j]);
+            }
+        }
+    }
+    let mut matches_by_scanner_pair = HashMap::<(usize, usize), PairMatches>::new();
+    for pairs in pairs_by_fingerprint.values() {
+        for &[scanner1, i, j] in pairs {
+            for &[scanner2, k, l] in pairs {
+                if scanner1 != scanner2 {
+                    matches_by_scanner_pair
+                        .entry((scanner1, scanner2))
+                        .or_default()
+                        .push([i, j, k, l]);
+                }
             }
         }
     }
-    panic!("No overlap found.");
+    let min_matches = min_shared_beacons * min_shared_beacons.saturating_sub(1) / 2;
+    matches_by_scanner_pair.retain(|_, matches| matches.len() >= min_matches);
+    matches_by_scanner_pair
 }
 
-fn try_overlapping_scanner_pair(
+fn fingerprint(displacement: Point) -> Fingerprint {
+    let Coord3(x, y, z) = displacement.abs();
+    let mut parts = [x, y, z];
+    parts.sort_unstable();
+    Coord3(parts[0], parts[1], parts[2])
+}
+
+/// Finds the rotations and translations which carry a matched pair of the
+/// new scanner's beacons onto the solved scanner's, then checks the most
+/// common ones against the full beacon lists.
+fn align_scanner(
     solved_scanner: &SolvedScanner,
-    solved_displacements_by_beacon: &[HashSet<Point>],
+    new_index: usize,
     new_beacons: &[Point],
-    new_displacements_by_beacon: &[HashSet<Point>],
+    matches: &[[usize; 4]],
+    min_shared_beacons: usize,
 ) -> Option<SolvedScanner> {
     let solved_beacons = &solved_scanner.absolute_beacons;
-    let mut corresponding_beacons = Vec::<(Point, Point)>::new();
-    for (i, solved_displacements) in solved_displacements_by_beacon.iter().enumerate() {
-        for (j, new_displacements) in new_displacements_by_beacon.iter().enumerate() {
-            if solved_displacements.intersection(new_displacements).count() > 11 {
-                corresponding_beacons.push((solved_beacons[i], new_beacons[j]));
-            }
+    let mut votes = HashMap::<(Rotation, Point), usize>::new();
+    for &[i, j, k, l] in matches {
+        let solved_displacement = solved_beacons[j] - solved_beacons[i];
+        let new_displacement = new_beacons[l] - new_beacons[k];
+        for rotation in Rotation::all() {
+            let rotated = rotation.apply(new_displacement);
+            let corresponding = if rotated == solved_displacement {
+                new_beacons[k]
+            } else if rotated == -solved_displacement {
+                new_beacons[l]
+            } else {
+                continue;
+            };
+            let location = solved_beacons[i] - rotation.apply(corresponding);
+            *votes.entry((rotation, location)).or_default() += 1;
         }
     }
-    if corresponding_beacons.len() < 12 {
-        return None;
-    }
-    for orientation in Orientation::all() {
-        let get_displacement = |(solved, new): (Point, Point)| solved - orientation.apply(new);
-        let target_displacement = get_displacement(corresponding_beacons[0]);
-        if corresponding_beacons[1..]
-            .iter()
-            .all(|&pair| get_displacement(pair) == target_displacement)
-        {
-            let location = target_displacement;
+    let mut candidates = votes.into_iter().collect::<Vec<_>>();
+    candidates.sort_unstable_by_key(|&(_, count)| cmp::Reverse(count));
+    let solved_set = solved_beacons.iter().collect::<HashSet<_>>();
+    candidates
+        .into_iter()
+        .map(|((orientation, location), _)| {
             let absolute_beacons = new_beacons
                 .iter()
                 .map(|&beacon| orientation.apply(beacon) + location)
-                .collect();
-            return Some(SolvedScanner {
+                .collect::<Vec<_>>();
+            SolvedScanner {
+                index: new_index,
+                parent: Some(solved_scanner.index),
                 location,
                 orientation,
                 absolute_beacons,
-            });
-        }
-    }
-    None
-}
-
-fn get_beacon_displacements(beacons: &[Point]) -> Vec<HashSet<Point>> {
-    beacons
-        .iter()
-        .map(|&beacon| {
-            beacons
+            }
+        })
+        .find(|scanner| {
+            let shared = scanner
+                .absolute_beacons
                 .iter()
-                .map(|&other| {
-                    let Coord3(x, y, z) = other - beacon;
-                    let mut coords = [x.abs(), y.abs(), z.abs()];
-                    coords.sort();
-                    Coord3(coords[0], coords[1], coords[2])
-                })
-                .collect()
+                .filter(|beacon| solved_set.contains(beacon))
+                .count();
+            shared >= min_shared_beacons
         })
-        .collect()
 }
 
-#[derive(Copy, Clone, Debug)]
-struct Orientation(i32, i32, i32);
-
-impl Orientation {
-    fn apply(self, Coord3(x, y, z): Point) -> Point {
-        let Self(o0, o1, o2) = self;
-        let parts = [0, x, y, z];
-        Coord3(
-            o0.signum() * parts[o0.abs() as usize],
-            o1.signum() * parts[o1.abs() as usize],
-            o2.signum() * parts[o2.abs() as usize],
-        )
-    }
-
-    fn all() -> Vec<Self> {
-        let mut result = Vec::with_capacity(24);
-        for (x1, y1, z1) in [
-            (1, 2, 3),
-            (-1, 3, 2),
-            (2, 3, 1),
-            (-2, 1, 3),
-            (3, 1, 2),
-            (-3, 2, 1),
-        ] {
-            for (x2, y2, z2) in [(1, 1, 1), (1, -1, -1), (-1, 1, -1), (-1, -1, 1)] {
-                result.push(Orientation(x1 * x2, y1 * y2, z1 * z2))
-            }
-        }
-        result
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_scanners() -> Vec<Vec<Point>> {
+        let text = include_str!("../../input/day19-test-input.txt");
+        parse_scanners(RawInput::from_test_input(text))
+    }
+
+    fn component_indices(solution: &PartialSolution) -> Vec<Vec<usize>> {
+        solution
+            .components
+            .iter()
+            .map(|component| {
+                let mut indices = component
+                    .scanners
+                    .iter()
+                    .map(|scanner| scanner.index)
+                    .collect::<Vec<_>>();
+                indices.sort_unstable();
+                indices
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_complete_solution() {
+        let solution = solve_completely(&test_scanners());
+        assert_eq!(solution.beacons.len(), 79);
+    }
+
+    #[test]
+    fn test_exports() {
+        let solution = solve_completely(&test_scanners());
+        let ply = solution.to_ply();
+        assert!(ply.starts_with("ply\nformat ascii 1.0\nelement vertex 84\n"));
+        assert_eq!(ply.lines().count(), 10 + 84);
+        assert!(ply.contains("\n68 -1246 -43 255 0 0\n"));
+        let csv = solution.to_csv();
+        assert_eq!(csv.lines().count(), 1 + 84);
+        assert!(csv.contains("\nscanner,1,68,-1246,-43\n"));
+        assert!(csv.contains("\nbeacon,,-892,524,684\n"));
+        let dot = solution.alignment_dot();
+        assert!(dot.contains("    0 -> 1;\n"));
+        assert!(dot.contains("    1 -> 4;\n"));
+        assert!(dot.contains("    4 -> 2;\n"));
+        assert_eq!(dot.matches("->").count(), 4);
+
+        let dir = std::env::temp_dir().join(format!("day19-export-{}", std::process::id()));
+        solution.export(&dir).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("beacons.ply")).unwrap(), ply);
+        assert_eq!(fs::read_to_string(dir.join("beacons.csv")).unwrap(), csv);
+        assert_eq!(fs::read_to_string(dir.join("alignment.dot")).unwrap(), dot);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_partial_solution() {
+        // Scanner 1 links scanner 0 to the rest, and scanner 3 only to it.
+        let mut scanners = test_scanners();
+        scanners[1].clear();
+        let solution = solve(&scanners, MIN_SHARED_BEACONS);
+        assert_eq!(component_indices(&solution), vec![vec![2, 4]]);
+        assert_eq!(solution.unsolved, vec![0, 1, 3]);
     }
-}
 
-impl Default for Orientation {
-    fn default() -> Self {
-        Self(0, 1, 2)
+    #[test]
+    fn test_threshold() {
+        let scanners = test_scanners();
+        let solution = solve(&scanners, MIN_SHARED_BEACONS + 1);
+        assert!(solution.components.is_empty());
+        assert_eq!(solution.unsolved, vec![0, 1, 2, 3, 4]);
+        let solution = solve(&scanners, 3);
+        assert_eq!(component_indices(&solution), vec![vec![0, 1, 2, 3, 4]]);
     }
 }
@@ -1,5 +1,7 @@
 use crate::harness::input::RawInput;
 use crate::regex;
+use crate::util::coords::Coord3;
+use crate::util::intervals::{Cuboid, CuboidSet};
 use crate::util::re::parse_with_regex;
 use ndarray::Array3;
 use std::collections::{BTreeSet, HashMap};
@@ -7,14 +9,26 @@ use std::str::FromStr;
 use std::{cmp, error};
 
 pub fn solve_part1(input: RawInput) -> i64 {
-    solve(input, true)
+    solve(input, true, Solver::DisjointCuboids)
 }
 
 pub fn solve_part2(input: RawInput) -> i64 {
-    solve(input, false)
+    solve(input, false, Solver::DisjointCuboids)
 }
 
-pub fn solve(input: RawInput, trim_region: bool) -> i64 {
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Solver {
+    /// Compresses coordinates to the breakpoints of every step and fills in a
+    /// dense grid of the resulting cells.
+    BreakpointGrid,
+    /// Keeps a list of signed cuboids, cancelling each step's overlap with
+    /// earlier cuboids by inclusion–exclusion.
+    SignedVolumes,
+    /// Keeps the cubes which are on as a `CuboidSet` of disjoint boxes.
+    DisjointCuboids,
+}
+
+pub fn solve(input: RawInput, trim_region: bool, solver: Solver) -> i64 {
     let mut steps = input.per_line(|line| line.single::<Step>());
     if trim_region {
         steps = steps
@@ -22,7 +36,15 @@ pub fn solve(input: RawInput, trim_region: bool) -> i64 {
             .map(|step| step.trim_to_initialization_region())
             .collect();
     }
-    let [x_breakpoints, y_breakpoints, z_breakpoints] = get_sorted_breakpoints(&steps);
+    match solver {
+        Solver::BreakpointGrid => solve_with_breakpoint_grid(&steps),
+        Solver::SignedVolumes => solve_with_signed_volumes(&steps),
+        Solver::DisjointCuboids => solve_with_disjoint_cuboids(&steps),
+    }
+}
+
+fn solve_with_breakpoint_grid(steps: &[Step]) -> i64 {
+    let [x_breakpoints, y_breakpoints, z_breakpoints] = get_sorted_breakpoints(steps);
     let x_indices_by_breakpoint = get_indices_by_breakpoints(&x_breakpoints);
     let y_indices_by_breakpoint = get_indices_by_breakpoints(&y_breakpoints);
     let z_indices_by_breakpoint = get_indices_by_breakpoints(&z_breakpoints);
@@ -61,6 +83,48 @@ pub fn solve(input: RawInput, trim_region: bool) -> i64 {
     total
 }
 
+fn solve_with_signed_volumes(steps: &[Step]) -> i64 {
+    let mut signs_by_cuboid = HashMap::<Cuboid<Coord3<i64>>, i64>::new();
+    for step in steps {
+        let cuboid = step.cuboid();
+        if cuboid.is_empty() {
+            continue;
+        }
+        let mut updates = HashMap::<Cuboid<Coord3<i64>>, i64>::new();
+        for (existing, &sign) in &signs_by_cuboid {
+            if let Some(overlap) = existing.intersection(&cuboid) {
+                *updates.entry(overlap).or_default() -= sign;
+            }
+        }
+        if step.is_on {
+            *updates.entry(cuboid).or_default() += 1;
+        }
+        for (cuboid, sign) in updates {
+            let total_sign = signs_by_cuboid.entry(cuboid).or_default();
+            *total_sign += sign;
+            if *total_sign == 0 {
+                signs_by_cuboid.remove(&cuboid);
+            }
+        }
+    }
+    signs_by_cuboid
+        .into_iter()
+        .map(|(cuboid, sign)| sign * cuboid.volume::<i64>())
+        .sum()
+}
+
+fn solve_with_disjoint_cuboids(steps: &[Step]) -> i64 {
+    let mut cubes_on = CuboidSet::new();
+    for step in steps {
+        if step.is_on {
+            cubes_on.insert(step.cuboid());
+        } else {
+            cubes_on.remove(step.cuboid());
+        }
+    }
+    cubes_on.volume()
+}
+
 #[derive(Copy, Clone, Debug)]
 struct Step {
     is_on: bool,
@@ -91,6 +155,13 @@ impl FromStr for Step {
 }
 
 impl Step {
+    fn cuboid(self) -> Cuboid<Coord3<i64>> {
+        Cuboid::inclusive(
+            Coord3(self.x_min, self.y_min, self.z_min),
+            Coord3(self.x_max, self.y_max, self.z_max),
+        )
+    }
+
     fn trim_to_initialization_region(self) -> Self {
         Self {
             is_on: self.is_on,
@@ -127,3 +198,26 @@ fn get_indices_by_breakpoints(breakpoints: &[i64]) -> HashMap<i64, usize> {
         .map(|(i, &x)| (x, i))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_input() -> RawInput<'static> {
+        let text = include_str!("../../input/day22-test-input.txt");
+        RawInput::from_test_input(text)
+    }
+
+    #[test]
+    fn test_solvers_agree() {
+        for (trim_region, expected) in [(true, 474140), (false, 2758514936282235)] {
+            for solver in [
+                Solver::BreakpointGrid,
+                Solver::SignedVolumes,
+                Solver::DisjointCuboids,
+            ] {
+                assert_eq!(solve(test_input(), trim_region, solver), expected);
+            }
+        }
+    }
+}
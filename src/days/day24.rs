@@ -1,14 +1,151 @@
+mod alu;
+mod optimizer;
+mod search;
+
 use crate::harness::input::RawInput;
+use alu::{Instruction, Operand, Register};
 
-pub fn solve_part1(_: RawInput) -> u64 {
-    98491959997994
+pub fn solve_part1(input: RawInput) -> u64 {
+    let program = alu::parse_program(input.as_str());
+    from_constraints(&program, |difference| {
+        if difference >= 0 {
+            (9 - difference, 9)
+        } else {
+            (9, 9 + difference)
+        }
+    })
+    .unwrap_or_else(|| search::max_accepted(&program).expect("No input is accepted."))
 }
 
-pub fn solve_part2(_: RawInput) -> u64 {
-    61191516111321
+pub fn solve_part2(input: RawInput) -> u64 {
+    let program = alu::parse_program(input.as_str());
+    from_constraints(&program, |difference| {
+        if difference >= 0 {
+            (1, 1 + difference)
+        } else {
+            (1 - difference, 1)
+        }
+    })
+    .unwrap_or_else(|| search::min_accepted(&program).expect("No input is accepted."))
+}
+
+/// The block of instructions repeated once per digit, as described in the
+/// comment at the end of this file.
+const BLOCK_TEMPLATE: &str = "inp w
+mul x 0
+add x z
+mod x 26
+div z {DIV}
+add x {CHECK}
+eql x w
+eql x 0
+mul y 0
+add y 25
+mul y x
+add y 1
+mul z y
+mul y 0
+add y w
+add y {OFFSET}
+mul y x
+add z y";
+
+/// The values which differ between blocks.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct BlockParams {
+    div: i64,
+    check: i64,
+    offset: i64,
+}
+
+/// Requires `digits[popped] == digits[pushed] + difference`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Constraint {
+    pushed: usize,
+    popped: usize,
+    difference: i64,
+}
+
+/// Derives the digit constraints from the program, picks the digits for each
+/// pair with `choose`, which maps a constraint's difference to the pushed and
+/// popped digits, and checks the result on the ALU. Returns `None` if the
+/// program doesn't have the structure described at the end of this file, or
+/// the chosen digits are out of range or rejected by the program.
+fn from_constraints(program: &[Instruction], choose: impl Fn(i64) -> (i64, i64)) -> Option<u64> {
+    let blocks = alu::split_at_inputs(program);
+    if blocks.iter().map(|block| block.len()).sum::<usize>() != program.len() {
+        return None;
+    }
+    let mut digits = vec![0; blocks.len()];
+    for constraint in derive_constraints(&blocks)? {
+        let (pushed, popped) = choose(constraint.difference);
+        if !(1..=9).contains(&pushed) || !(1..=9).contains(&popped) {
+            return None;
+        }
+        digits[constraint.pushed] = pushed;
+        digits[constraint.popped] = popped;
+    }
+    let registers = alu::run(program, digits.iter().copied()).ok()?;
+    (registers[Register::Z] == 0).then_some(())?;
+    Some(digits.iter().fold(0, |acc, &digit| 10 * acc + digit as u64))
+}
+
+/// Plays out the stack of base-26 digits held in `z`, pairing each block which
+/// pops with the block which pushed the value it pops.
+fn derive_constraints(blocks: &[&[Instruction]]) -> Option<Vec<Constraint>> {
+    let mut stack = vec![];
+    let mut constraints = vec![];
+    for (i, block) in blocks.iter().enumerate() {
+        let params = block_params(block)?;
+        match params.div {
+            // A positive check above 9 can never match a digit.
+            1 if params.check > 9 => stack.push((i, params.offset)),
+            26 => {
+                let (pushed, offset) = stack.pop()?;
+                constraints.push(Constraint {
+                    pushed,
+                    popped: i,
+                    difference: offset + params.check,
+                });
+            }
+            _ => return None,
+        }
+    }
+    if stack.is_empty() {
+        Some(constraints)
+    } else {
+        None
+    }
+}
+
+/// Reads the parameters of a block, or returns `None` if it differs from the
+/// template elsewhere.
+fn block_params(block: &[Instruction]) -> Option<BlockParams> {
+    let literal = |index: usize| match block.get(index)? {
+        Instruction::Binary(_, _, Operand::Literal(value)) => Some(*value),
+        _ => None,
+    };
+    let params = BlockParams {
+        div: literal(4)?,
+        check: literal(5)?,
+        offset: literal(15)?,
+    };
+    let expected = alu::parse_program(
+        &BLOCK_TEMPLATE
+            .replace("{DIV}", &params.div.to_string())
+            .replace("{CHECK}", &params.check.to_string())
+            .replace("{OFFSET}", &params.offset.to_string()),
+    );
+    if block == expected {
+        Some(params)
+    } else {
+        None
+    }
 }
 
 /*
+The code above automates the following analysis, which was first done by hand.
+
 The input is a set of instructions repeated 14 times. The repetitions differ
 only in three places, marked below as {DIV}, {CHECK}, and {OFFSET}:
 
@@ -125,3 +262,38 @@ Finding the smallest number is equivalent, e.g the same rule tells us that the
 sixth digit should be 5 and the fifth digit should be 1. This gives us the
 smallest possible number: 61191516111321.
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_agrees_with_constraints() {
+        let text = include_str!("../../input/day24-input.txt");
+        let program = alu::parse_program(text);
+        assert_eq!(
+            search::max_accepted(&program),
+            Some(solve_part1(RawInput::new(text)))
+        );
+        assert_eq!(
+            search::min_accepted(&program),
+            Some(solve_part2(RawInput::new(text)))
+        );
+    }
+
+    #[test]
+    fn test_falls_back_when_analysis_fails() {
+        // The pushed value can reach 26, which breaks the base-26 stack the
+        // analysis assumes, so the digits it picks are rejected.
+        let block = |div: i64, check: i64, offset: i64| {
+            BLOCK_TEMPLATE
+                .replace("{DIV}", &div.to_string())
+                .replace("{CHECK}", &check.to_string())
+                .replace("{OFFSET}", &offset.to_string())
+        };
+        let text = [block(1, 10, 20), block(26, -25, 0)].join("\n");
+        let program = alu::parse_program(&text);
+        assert_eq!(from_constraints(&program, |_| (9, 4)), None);
+        assert_eq!(search::max_accepted(&program), None);
+    }
+}
@@ -0,0 +1,266 @@
+use crate::string_enum;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+string_enum!(pub Register {
+    W = "w",
+    X = "x",
+    Y = "y",
+    Z = "z",
+});
+
+string_enum!(pub BinaryOp {
+    Add = "add",
+    Mul = "mul",
+    Div = "div",
+    Mod = "mod",
+    Eql = "eql",
+});
+
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Operand {
+    Register(Register),
+    Literal(i64),
+}
+
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Instruction {
+    Inp(Register),
+    Binary(BinaryOp, Register, Operand),
+}
+
+/// Why the ALU stopped before the end of a program.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AluError {
+    MissingInput,
+    DivisionByZero,
+    /// `mod` with a negative dividend or a non-positive divisor.
+    InvalidModulus,
+    /// A result outside the range of `i64`.
+    Overflow,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseInstructionError(String);
+
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Registers(pub [i64; 4]);
+
+impl Register {
+    pub const ALL: [Register; 4] = [Register::W, Register::X, Register::Y, Register::Z];
+
+    pub fn index(self) -> usize {
+        match self {
+            Register::W => 0,
+            Register::X => 1,
+            Register::Y => 2,
+            Register::Z => 3,
+        }
+    }
+}
+
+impl BinaryOp {
+    pub fn apply(self, a: i64, b: i64) -> Result<i64, AluError> {
+        match self {
+            BinaryOp::Add => a.checked_add(b).ok_or(AluError::Overflow),
+            BinaryOp::Mul => a.checked_mul(b).ok_or(AluError::Overflow),
+            BinaryOp::Div if b == 0 => Err(AluError::DivisionByZero),
+            BinaryOp::Div => a.checked_div(b).ok_or(AluError::Overflow),
+            BinaryOp::Mod if a < 0 || b <= 0 => Err(AluError::InvalidModulus),
+            BinaryOp::Mod => Ok(a % b),
+            BinaryOp::Eql => Ok((a == b) as i64),
+        }
+    }
+}
+
+impl Registers {
+    pub fn get(&self, operand: Operand) -> i64 {
+        match operand {
+            Operand::Register(register) => self[register],
+            Operand::Literal(value) => value,
+        }
+    }
+
+    /// Runs a single instruction, taking input from `inputs` if needed.
+    pub fn step(
+        &mut self,
+        instruction: Instruction,
+        inputs: &mut impl Iterator<Item = i64>,
+    ) -> Result<(), AluError> {
+        match instruction {
+            Instruction::Inp(register) => {
+                self[register] = inputs.next().ok_or(AluError::MissingInput)?;
+            }
+            Instruction::Binary(op, register, operand) => {
+                self[register] = op.apply(self[register], self.get(operand))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Index<Register> for Registers {
+    type Output = i64;
+
+    fn index(&self, register: Register) -> &i64 {
+        &self.0[register.index()]
+    }
+}
+
+impl IndexMut<Register> for Registers {
+    fn index_mut(&mut self, register: Register) -> &mut i64 {
+        &mut self.0[register.index()]
+    }
+}
+
+/// Runs a program from zeroed registers, reading each `inp` from `inputs`.
+pub fn run(
+    program: &[Instruction],
+    inputs: impl IntoIterator<Item = i64>,
+) -> Result<Registers, AluError> {
+    let mut registers = Registers::default();
+    let mut inputs = inputs.into_iter();
+    for &instruction in program {
+        registers.step(instruction, &mut inputs)?;
+    }
+    Ok(registers)
+}
+
+pub fn parse_program(s: &str) -> Vec<Instruction> {
+    s.lines().map(|line| line.parse().unwrap()).collect()
+}
+
+/// Splits a program into the runs of instructions starting at each `inp`.
+pub fn split_at_inputs(program: &[Instruction]) -> Vec<&[Instruction]> {
+    let starts = program
+        .iter()
+        .enumerate()
+        .filter(|(_, instruction)| matches!(instruction, Instruction::Inp(_)))
+        .map(|(i, _)| i)
+        .chain([program.len()])
+        .collect::<Vec<_>>();
+    starts
+        .windows(2)
+        .map(|window| &program[window[0]..window[1]])
+        .collect()
+}
+
+impl Display for ParseInstructionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid instruction {:?}.", self.0)
+    }
+}
+
+impl std::error::Error for ParseInstructionError {}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Register(register) => write!(f, "{}", register),
+            Operand::Literal(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Inp(register) => write!(f, "inp {}", register),
+            Instruction::Binary(op, register, operand) => {
+                write!(f, "{} {} {}", op, register, operand)
+            }
+        }
+    }
+}
+
+impl FromStr for Operand {
+    type Err = ParseInstructionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Operand::Register)
+            .or_else(|_| s.parse().map(Operand::Literal))
+            .map_err(|_| ParseInstructionError(s.to_owned()))
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = ParseInstructionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseInstructionError(s.to_owned());
+        let parts = s.split_ascii_whitespace().collect::<Vec<_>>();
+        match parts[..] {
+            ["inp", register] => Ok(Instruction::Inp(register.parse().map_err(|_| error())?)),
+            [op, register, operand] => Ok(Instruction::Binary(
+                op.parse().map_err(|_| error())?,
+                register.parse().map_err(|_| error())?,
+                operand.parse().map_err(|_| error())?,
+            )),
+            _ => Err(error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        let negate = parse_program("inp x\nmul x -1");
+        assert_eq!(run(&negate, [7]).unwrap()[Register::X], -7);
+        let is_triple = parse_program("inp z\ninp x\nmul z 3\neql z x");
+        assert_eq!(run(&is_triple, [2, 6]).unwrap()[Register::Z], 1);
+        assert_eq!(run(&is_triple, [2, 5]).unwrap()[Register::Z], 0);
+        let bits = parse_program(
+            "inp w\nadd z w\nmod z 2\ndiv w 2\nadd y w\nmod y 2\ndiv w 2\nadd x w\nmod x 2\ndiv w 2\nmod w 2",
+        );
+        assert_eq!(run(&bits, [13]).unwrap(), Registers([1, 1, 0, 1]));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            run(&parse_program("inp w"), []),
+            Err(AluError::MissingInput)
+        );
+        assert_eq!(
+            run(&parse_program("div x 0"), []),
+            Err(AluError::DivisionByZero)
+        );
+        assert_eq!(
+            run(&parse_program("add x -1\nmod x 2"), []),
+            Err(AluError::InvalidModulus)
+        );
+        assert_eq!(
+            run(
+                &parse_program("inp x\nmul x 4611686018427387904\nmul x 2"),
+                [1]
+            ),
+            Err(AluError::Overflow)
+        );
+        assert_eq!(
+            run(
+                &parse_program("add x -9223372036854775807\nadd x -1\ndiv x -1"),
+                []
+            ),
+            Err(AluError::Overflow)
+        );
+        assert_eq!(
+            "jmp x 2".parse::<Instruction>().unwrap_err().to_string(),
+            "Invalid instruction \"jmp x 2\"."
+        );
+        assert!("add x".parse::<Instruction>().is_err());
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let text = "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z -26\neql x w";
+        let program = parse_program(text);
+        let printed = program.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        assert_eq!(printed.join("\n"), text);
+        assert_eq!(split_at_inputs(&program).len(), 1);
+    }
+}
@@ -0,0 +1,359 @@
+use super::alu::{AluError, BinaryOp, Instruction, Operand, Registers};
+
+/// A compact instruction for the optimized form of a program, with registers
+/// given by index. Operations are specialized by operand kind so that running
+/// one takes a single dispatch.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Op {
+    Input(usize),
+    Set(usize, i64),
+    Copy(usize, usize),
+    AddLit(usize, i64),
+    AddReg(usize, usize),
+    MulLit(usize, i64),
+    MulReg(usize, usize),
+    DivLit(usize, i64),
+    DivReg(usize, usize),
+    ModLit(usize, i64),
+    ModReg(usize, usize),
+    EqlLit(usize, i64),
+    EqlReg(usize, usize),
+    /// `eql` followed by `eql _ 0`, the idiom for "not equal".
+    NeqLit(usize, i64),
+    NeqReg(usize, usize),
+}
+
+/// An optimized program, split into the ops run before the first input and
+/// one block for each input. Running it gives the same registers as the
+/// original program, or the same error.
+#[derive(Clone, Debug)]
+pub struct Compiled {
+    pub prefix: Vec<Op>,
+    pub blocks: Vec<Vec<Op>>,
+}
+
+impl Compiled {
+    pub fn new(program: &[Instruction]) -> Self {
+        let ops = fuse_not_equal(&remove_dead_code(&fold_constants(program)));
+        let mut prefix = vec![];
+        let mut blocks: Vec<Vec<Op>> = vec![];
+        for op in ops {
+            match (op, blocks.last_mut()) {
+                (Op::Input(_), _) => blocks.push(vec![op]),
+                (_, Some(block)) => block.push(op),
+                (_, None) => prefix.push(op),
+            }
+        }
+        Self { prefix, blocks }
+    }
+
+    pub fn run(&self, inputs: impl IntoIterator<Item = i64>) -> Result<Registers, AluError> {
+        let mut registers = Registers::default();
+        execute(&self.prefix, &mut registers, 0)?;
+        let mut inputs = inputs.into_iter();
+        for block in &self.blocks {
+            let input = inputs.next().ok_or(AluError::MissingInput)?;
+            execute(block, &mut registers, input)?;
+        }
+        Ok(registers)
+    }
+
+    pub fn len(&self) -> usize {
+        self.prefix.len() + self.blocks.iter().map(|block| block.len()).sum::<usize>()
+    }
+}
+
+/// Runs ops, each `Input` reading `input`.
+pub fn execute(ops: &[Op], registers: &mut Registers, input: i64) -> Result<(), AluError> {
+    let r = &mut registers.0;
+    for &op in ops {
+        match op {
+            Op::Input(a) => r[a] = input,
+            Op::Set(a, value) => r[a] = value,
+            Op::Copy(a, b) => r[a] = r[b],
+            Op::AddLit(a, value) => r[a] = BinaryOp::Add.apply(r[a], value)?,
+            Op::AddReg(a, b) => r[a] = BinaryOp::Add.apply(r[a], r[b])?,
+            Op::MulLit(a, value) => r[a] = BinaryOp::Mul.apply(r[a], value)?,
+            Op::MulReg(a, b) => r[a] = BinaryOp::Mul.apply(r[a], r[b])?,
+            Op::DivLit(a, value) => r[a] = BinaryOp::Div.apply(r[a], value)?,
+            Op::DivReg(a, b) => r[a] = BinaryOp::Div.apply(r[a], r[b])?,
+            Op::ModLit(a, value) => r[a] = BinaryOp::Mod.apply(r[a], value)?,
+            Op::ModReg(a, b) => r[a] = BinaryOp::Mod.apply(r[a], r[b])?,
+            Op::EqlLit(a, value) => r[a] = (r[a] == value) as i64,
+            Op::EqlReg(a, b) => r[a] = (r[a] == r[b]) as i64,
+            Op::NeqLit(a, value) => r[a] = (r[a] != value) as i64,
+            Op::NeqReg(a, b) => r[a] = (r[a] != r[b]) as i64,
+        }
+    }
+    Ok(())
+}
+
+impl Op {
+    fn binary(op: BinaryOp, a: usize, operand: Operand) -> Self {
+        match (op, operand) {
+            (BinaryOp::Add, Operand::Literal(value)) => Op::AddLit(a, value),
+            (BinaryOp::Add, Operand::Register(b)) => Op::AddReg(a, b.index()),
+            (BinaryOp::Mul, Operand::Literal(value)) => Op::MulLit(a, value),
+            (BinaryOp::Mul, Operand::Register(b)) => Op::MulReg(a, b.index()),
+            (BinaryOp::Div, Operand::Literal(value)) => Op::DivLit(a, value),
+            (BinaryOp::Div, Operand::Register(b)) => Op::DivReg(a, b.index()),
+            (BinaryOp::Mod, Operand::Literal(value)) => Op::ModLit(a, value),
+            (BinaryOp::Mod, Operand::Register(b)) => Op::ModReg(a, b.index()),
+            (BinaryOp::Eql, Operand::Literal(value)) => Op::EqlLit(a, value),
+            (BinaryOp::Eql, Operand::Register(b)) => Op::EqlReg(a, b.index()),
+        }
+    }
+
+    /// The register written, and the registers read.
+    fn registers(self) -> (usize, Option<usize>, Option<usize>) {
+        match self {
+            Op::Input(a) | Op::Set(a, _) => (a, None, None),
+            Op::Copy(a, b) => (a, Some(b), None),
+            Op::AddLit(a, _)
+            | Op::MulLit(a, _)
+            | Op::DivLit(a, _)
+            | Op::ModLit(a, _)
+            | Op::EqlLit(a, _)
+            | Op::NeqLit(a, _) => (a, Some(a), None),
+            Op::AddReg(a, b)
+            | Op::MulReg(a, b)
+            | Op::DivReg(a, b)
+            | Op::ModReg(a, b)
+            | Op::EqlReg(a, b)
+            | Op::NeqReg(a, b) => (a, Some(a), Some(b)),
+        }
+    }
+
+    /// Whether the op must be kept even if its result is unused, because it
+    /// consumes an input or might crash, including by overflowing.
+    fn has_effect(self) -> bool {
+        match self {
+            Op::Input(_)
+            | Op::AddReg(..)
+            | Op::MulReg(..)
+            | Op::DivReg(..)
+            | Op::ModLit(..)
+            | Op::ModReg(..) => true,
+            Op::AddLit(_, value) => value != 0,
+            Op::MulLit(_, value) => !(0..=1).contains(&value),
+            Op::DivLit(_, value) => value == 0 || value == -1,
+            Op::Set(..)
+            | Op::Copy(..)
+            | Op::EqlLit(..)
+            | Op::EqlReg(..)
+            | Op::NeqLit(..)
+            | Op::NeqReg(..) => false,
+        }
+    }
+}
+
+/// Translates the program to ops, replacing operations on registers with
+/// known values by their results and dropping identities such as `add x 0`.
+/// Registers start at zero and become unknown when read from input.
+fn fold_constants(program: &[Instruction]) -> Vec<Op> {
+    let mut known = [Some(0); 4];
+    let mut ops = vec![];
+    for &instruction in program {
+        let (op, register, operand) = match instruction {
+            Instruction::Inp(register) => {
+                ops.push(Op::Input(register.index()));
+                known[register.index()] = None;
+                continue;
+            }
+            Instruction::Binary(op, register, operand) => (op, register.index(), operand),
+        };
+        let a = known[register];
+        let b = match operand {
+            Operand::Literal(value) => Some(value),
+            Operand::Register(source) => known[source.index()],
+        };
+        known[register] = match (op, a, b) {
+            (_, Some(a), Some(b)) => match op.apply(a, b) {
+                Ok(value) => {
+                    if value != a {
+                        ops.push(Op::Set(register, value));
+                    }
+                    Some(value)
+                }
+                Err(_) => {
+                    ops.push(Op::binary(op, register, Operand::Literal(b)));
+                    None
+                }
+            },
+            (BinaryOp::Mul, _, Some(0)) | (BinaryOp::Mul, Some(0), _) => {
+                ops.push(Op::Set(register, 0));
+                Some(0)
+            }
+            (BinaryOp::Add, _, Some(0))
+            | (BinaryOp::Mul, _, Some(1))
+            | (BinaryOp::Div, _, Some(1)) => a,
+            (_, _, Some(b)) => {
+                ops.push(Op::binary(op, register, Operand::Literal(b)));
+                None
+            }
+            (BinaryOp::Add | BinaryOp::Mul, Some(a), None) => {
+                // Commute, so that the known value becomes a literal.
+                let Operand::Register(source) = operand else {
+                    unreachable!()
+                };
+                ops.push(Op::Copy(register, source.index()));
+                if (op, a) != (BinaryOp::Add, 0) && (op, a) != (BinaryOp::Mul, 1) {
+                    ops.push(Op::binary(op, register, Operand::Literal(a)));
+                }
+                None
+            }
+            _ => {
+                ops.push(Op::binary(op, register, operand));
+                None
+            }
+        };
+    }
+    ops
+}
+
+/// Removes ops whose results are overwritten or never read, keeping every
+/// register's final value.
+fn remove_dead_code(ops: &[Op]) -> Vec<Op> {
+    let mut live = [true; 4];
+    let mut result = vec![];
+    for &op in ops.iter().rev() {
+        let (written, read1, read2) = op.registers();
+        if !live[written] && !op.has_effect() {
+            continue;
+        }
+        live[written] = false;
+        for read in [read1, read2].into_iter().flatten() {
+            live[read] = true;
+        }
+        result.push(op);
+    }
+    result.reverse();
+    result
+}
+
+/// Replaces `eql` followed by `eql _ 0` on the same register by one op.
+fn fuse_not_equal(ops: &[Op]) -> Vec<Op> {
+    let mut result: Vec<Op> = vec![];
+    for &op in ops {
+        let fused = match (result.last(), op) {
+            (Some(&Op::EqlLit(a, value)), Op::EqlLit(b, 0)) if a == b => Op::NeqLit(a, value),
+            (Some(&Op::EqlReg(a, c)), Op::EqlLit(b, 0)) if a == b => Op::NeqReg(a, c),
+            _ => {
+                result.push(op);
+                continue;
+            }
+        };
+        *result.last_mut().unwrap() = fused;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::alu::{self, parse_program};
+    use super::*;
+    use crate::util::random::Random;
+
+    /// Digit vectors for comparing the two forms.
+    fn random_inputs(count: usize, len: usize) -> Vec<Vec<i64>> {
+        let mut random = Random::new(0x853c_49e6_748f_ea9b);
+        (0..count)
+            .map(|_| (0..len).map(|_| random.below(9) as i64 + 1).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_folding_and_dead_code() {
+        let program = parse_program("mul x 0\nadd x z\ninp w\nmul y 0\nadd y 25\nmul y w\nadd y 1");
+        let compiled = Compiled::new(&program);
+        assert_eq!(compiled.prefix, vec![]);
+        assert_eq!(
+            compiled.blocks,
+            vec![vec![
+                Op::Input(0),
+                Op::Copy(2, 0),
+                Op::MulLit(2, 25),
+                Op::AddLit(2, 1)
+            ]]
+        );
+        let not_equal = parse_program("inp w\ninp x\neql x w\neql x 0");
+        assert_eq!(
+            Compiled::new(&not_equal).blocks[1],
+            vec![Op::Input(1), Op::NeqReg(1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_preserves_crashes() {
+        let overflows = "inp x\nmul x 4611686018427387904\nmul x 2\nmul x 0";
+        let negates_min = "add x -9223372036854775807\nadd x -1\nmul x -1\nmul x 0";
+        for text in [
+            "div x 0",
+            "add x -3\nmod x 5",
+            "inp w\nmod x w\nmul x 0",
+            overflows,
+            negates_min,
+        ] {
+            let program = parse_program(text);
+            for input in [0, 3] {
+                assert_eq!(
+                    Compiled::new(&program).run([input]),
+                    alu::run(&program, [input]),
+                    "{}",
+                    text
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_interpreter() {
+        let text = include_str!("../../../input/day24-input.txt");
+        let program = parse_program(text);
+        let compiled = Compiled::new(&program);
+        assert!(compiled.len() < program.len());
+        for inputs in random_inputs(200, 14) {
+            assert_eq!(
+                compiled.run(inputs.iter().copied()),
+                alu::run(&program, inputs.iter().copied())
+            );
+        }
+    }
+
+    /// Prints how long a million runs of the puzzle input take interpreted
+    /// and compiled. Use `cargo test --release bench_compiled -- --ignored
+    /// --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_compiled() {
+        use std::hint::black_box;
+        use std::time::Instant;
+
+        let program = parse_program(include_str!("../../../input/day24-input.txt"));
+        let compiled = Compiled::new(&program);
+        let all_inputs = random_inputs(1_000_000, 14);
+        for _ in 0..3 {
+            let start_time = Instant::now();
+            let naive_sum: i64 = all_inputs
+                .iter()
+                .map(|inputs| {
+                    alu::run(black_box(&program), inputs.iter().copied())
+                        .unwrap()
+                        .0[3]
+                })
+                .sum();
+            let naive_duration = start_time.elapsed();
+            let start_time = Instant::now();
+            let compiled_sum: i64 = all_inputs
+                .iter()
+                .map(|inputs| compiled.run(black_box(inputs.iter().copied())).unwrap().0[3])
+                .sum();
+            let compiled_duration = start_time.elapsed();
+            assert_eq!(naive_sum, compiled_sum);
+            println!(
+                "Interpreted: {:.2?}, compiled: {:.2?}",
+                naive_duration, compiled_duration
+            );
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use super::alu::{BinaryOp, Instruction, Operand, Register, Registers};
+use super::optimizer::{self, Compiled};
+use std::cmp;
+use std::collections::HashSet;
+
+/// Finds the largest input of digits 1 to 9 which the program accepts, by
+/// leaving zero in `z` without crashing.
+pub fn max_accepted(program: &[Instruction]) -> Option<u64> {
+    Search::new(program, [9, 8, 7, 6, 5, 4, 3, 2, 1]).run()
+}
+
+/// Finds the smallest input of digits 1 to 9 which the program accepts.
+pub fn min_accepted(program: &[Instruction]) -> Option<u64> {
+    Search::new(program, [1, 2, 3, 4, 5, 6, 7, 8, 9]).run()
+}
+
+/// The inclusive range of values a register may hold.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct ValueRange {
+    min: i64,
+    max: i64,
+}
+
+const DIGITS: ValueRange = ValueRange { min: 1, max: 9 };
+
+/// A depth-first search over the digits, one block of the program per digit.
+/// States known to lead nowhere are remembered, keyed by the block and the
+/// registers the rest of the program reads before writing. For programs like
+/// MONAD, which reset every register but `z` in each block, that is just `z`.
+/// Digits are tried by running the compiled form of each block.
+struct Search<'a> {
+    blocks: Vec<&'a [Instruction]>,
+    compiled: Compiled,
+    /// For each block, which registers it or a later block reads before
+    /// writing.
+    live_registers: Vec<[bool; 4]>,
+    digit_order: [i64; 9],
+    dead_ends: HashSet<(usize, Registers)>,
+}
+
+impl<'a> Search<'a> {
+    fn new(program: &'a [Instruction], digit_order: [i64; 9]) -> Self {
+        let first_input = program
+            .iter()
+            .position(|instruction| matches!(instruction, Instruction::Inp(_)))
+            .unwrap_or(program.len());
+        let blocks = super::alu::split_at_inputs(&program[first_input..]);
+        let mut live_registers = vec![[false; 4]; blocks.len()];
+        let mut live = [false; 4];
+        live[Register::Z.index()] = true;
+        for (i, block) in blocks.iter().enumerate().rev() {
+            live = live_before(block, live);
+            live_registers[i] = live;
+        }
+        Self {
+            blocks,
+            compiled: Compiled::new(program),
+            live_registers,
+            digit_order,
+            dead_ends: HashSet::new(),
+        }
+    }
+
+    fn run(mut self) -> Option<u64> {
+        let mut registers = Registers::default();
+        optimizer::execute(&self.compiled.prefix, &mut registers, 0).ok()?;
+        let mut digits = vec![];
+        if self.search(0, registers, &mut digits) {
+            Some(digits.iter().fold(0, |acc, &digit| 10 * acc + digit as u64))
+        } else {
+            None
+        }
+    }
+
+    /// Tries each digit for the block, pushing the accepted ones onto
+    /// `digits`. Returns whether an accepted input was found.
+    fn search(&mut self, block: usize, registers: Registers, digits: &mut Vec<i64>) -> bool {
+        if block == self.blocks.len() {
+            return registers[Register::Z] == 0;
+        }
+        let key = (block, self.live_part(block, registers));
+        if self.dead_ends.contains(&key) || !self.may_accept(block, registers) {
+            return false;
+        }
+        for digit in self.digit_order {
+            let mut next = registers;
+            if optimizer::execute(&self.compiled.blocks[block], &mut next, digit).is_ok() {
+                digits.push(digit);
+                if self.search(block + 1, next, digits) {
+                    return true;
+                }
+                digits.pop();
+            }
+        }
+        self.dead_ends.insert(key);
+        false
+    }
+
+    fn live_part(&self, block: usize, mut registers: Registers) -> Registers {
+        for register in Register::ALL {
+            if !self.live_registers[block][register.index()] {
+                registers[register] = 0;
+            }
+        }
+        registers
+    }
+
+    /// Runs range analysis from `block` onward, with every input unknown, to
+    /// check whether `z` could end at zero.
+    fn may_accept(&self, block: usize, registers: Registers) -> bool {
+        let mut ranges = registers.0.map(|value| ValueRange {
+            min: value,
+            max: value,
+        });
+        for &instruction in self.blocks[block..].iter().flat_map(|block| block.iter()) {
+            match analyze(instruction, ranges) {
+                Some(next) => ranges = next,
+                None => return false,
+            }
+        }
+        let z = ranges[Register::Z.index()];
+        z.min <= 0 && 0 <= z.max
+    }
+}
+
+/// Updates which registers are live, reading backward through `block`.
+fn live_before(block: &[Instruction], mut live: [bool; 4]) -> [bool; 4] {
+    for &instruction in block.iter().rev() {
+        match instruction {
+            Instruction::Inp(register) => live[register.index()] = false,
+            Instruction::Binary(op, register, operand) => {
+                let overwrites = matches!((op, operand), (BinaryOp::Mul, Operand::Literal(0)));
+                if overwrites {
+                    live[register.index()] = false;
+                } else {
+                    live[register.index()] = true;
+                    if let Operand::Register(source) = operand {
+                        live[source.index()] = true;
+                    }
+                }
+            }
+        }
+    }
+    live
+}
+
+/// Computes the ranges after an instruction, or `None` if it must crash.
+fn analyze(instruction: Instruction, mut ranges: [ValueRange; 4]) -> Option<[ValueRange; 4]> {
+    match instruction {
+        Instruction::Inp(register) => ranges[register.index()] = DIGITS,
+        Instruction::Binary(op, register, operand) => {
+            let a = ranges[register.index()];
+            let b = match operand {
+                Operand::Register(source) => ranges[source.index()],
+                Operand::Literal(value) => ValueRange {
+                    min: value,
+                    max: value,
+                },
+            };
+            ranges[register.index()] = analyze_binary(op, a, b)?;
+        }
+    }
+    Some(ranges)
+}
+
+fn analyze_binary(op: BinaryOp, a: ValueRange, b: ValueRange) -> Option<ValueRange> {
+    let corners = |f: fn(i64, i64) -> i64, bs: &[i64]| {
+        let mut result = ValueRange {
+            min: i64::MAX,
+            max: i64::MIN,
+        };
+        for x in [a.min, a.max] {
+            for &y in bs {
+                let value = f(x, y);
+                result.min = cmp::min(result.min, value);
+                result.max = cmp::max(result.max, value);
+            }
+        }
+        result
+    };
+    match op {
+        BinaryOp::Add => Some(ValueRange {
+            min: a.min.saturating_add(b.min),
+            max: a.max.saturating_add(b.max),
+        }),
+        BinaryOp::Mul => Some(corners(i64::saturating_mul, &[b.min, b.max])),
+        BinaryOp::Div => {
+            // Truncating division is monotonic on each side of zero, so the
+            // extremes lie at the ends of the nonzero parts of the divisor.
+            let mut divisors = [0; 4];
+            let mut n_divisors = 0;
+            for d in [b.min, b.max, -1, 1] {
+                if d != 0 && b.min <= d && d <= b.max {
+                    divisors[n_divisors] = d;
+                    n_divisors += 1;
+                }
+            }
+            if n_divisors == 0 {
+                None
+            } else {
+                Some(corners(i64::saturating_div, &divisors[..n_divisors]))
+            }
+        }
+        BinaryOp::Mod => {
+            let a = ValueRange {
+                min: cmp::max(a.min, 0),
+                max: a.max,
+            };
+            let b_max = b.max;
+            if a.min > a.max || b_max < 1 {
+                None
+            } else if a.max < cmp::max(b.min, 1) {
+                Some(a)
+            } else {
+                Some(ValueRange {
+                    min: 0,
+                    max: cmp::min(a.max, b_max - 1),
+                })
+            }
+        }
+        BinaryOp::Eql => {
+            if a.min == a.max && b.min == b.max && a.min == b.min {
+                Some(ValueRange { min: 1, max: 1 })
+            } else if a.max < b.min || b.max < a.min {
+                Some(ValueRange { min: 0, max: 0 })
+            } else {
+                Some(ValueRange { min: 0, max: 1 })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::alu::{self, parse_program};
+    use super::*;
+
+    /// Checks the search against trying every input.
+    fn brute_force(program: &[Instruction], n_digits: u32) -> (Option<u64>, Option<u64>) {
+        let accepted = (0..9_u64.pow(n_digits))
+            .map(|mut n| {
+                let mut digits = vec![];
+                for _ in 0..n_digits {
+                    digits.push((n % 9 + 1) as i64);
+                    n /= 9;
+                }
+                digits.reverse();
+                digits
+            })
+            .filter(|digits| {
+                alu::run(program, digits.iter().copied())
+                    .is_ok_and(|registers| registers[Register::Z] == 0)
+            })
+            .map(|digits| digits.iter().fold(0, |acc, &d| 10 * acc + d as u64))
+            .collect::<Vec<_>>();
+        (
+            accepted.iter().copied().max(),
+            accepted.iter().copied().min(),
+        )
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let programs = [
+            // Accepts inputs whose digits sum to 12, carrying state in `x`.
+            "inp w\nadd x w\ninp w\nadd x w\ninp w\nadd x w\nadd z x\nadd z -12",
+            // Crashes unless the first digit exceeds the second.
+            "inp x\ninp y\nmul y -1\nadd y x\nadd y -1\nmod y 3\nadd z y\ninp w\nadd w -5\nmul w w\nadd z w",
+            // Divides by a digit difference and never accepts anything else.
+            "inp x\ninp y\nadd x -4\ndiv y x\nadd z y\nadd z -2\ninp w\nmod w 1",
+            "inp w\nadd z 1",
+        ];
+        for text in programs {
+            let program = parse_program(text);
+            let n_digits = super::super::alu::split_at_inputs(&program).len() as u32;
+            let expected = brute_force(&program, n_digits);
+            assert_eq!(
+                (max_accepted(&program), min_accepted(&program)),
+                expected,
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_ranges() {
+        let range = |min, max| ValueRange { min, max };
+        assert_eq!(
+            analyze_binary(BinaryOp::Div, range(-7, 20), range(-2, 3)),
+            Some(range(-20, 20))
+        );
+        assert_eq!(
+            analyze_binary(BinaryOp::Div, range(1, 2), range(0, 0)),
+            None
+        );
+        assert_eq!(
+            analyze_binary(BinaryOp::Mod, range(3, 5), range(26, 26)),
+            Some(range(3, 5))
+        );
+        assert_eq!(
+            analyze_binary(BinaryOp::Mod, range(-5, 100), range(26, 26)),
+            Some(range(0, 25))
+        );
+        assert_eq!(
+            analyze_binary(BinaryOp::Mod, range(-5, -1), range(2, 2)),
+            None
+        );
+        assert_eq!(
+            analyze_binary(BinaryOp::Mul, range(-3, 2), range(-4, 5)),
+            Some(range(-15, 12))
+        );
+    }
+}
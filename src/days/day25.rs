@@ -1,11 +1,9 @@
 use crate::harness::input::RawInput;
-use crate::util::grid::Grid;
-use ndarray::Array2;
-use std::collections::HashSet;
+use crate::util::grid::{Grid, Offset};
 
 pub fn solve_part1(input: RawInput) -> usize {
     let bytes = Grid::parse_bytes(input.as_str());
-    let mut state = State(bytes.0.map(|&b| b.into()));
+    let mut state = State(bytes.map(|&b| b.into()));
     let mut step_count = 1;
     while state.advance() {
         step_count += 1;
@@ -36,47 +34,27 @@ impl From<u8> for Space {
 }
 
 #[derive(Debug)]
-struct State(Array2<Space>);
+struct State(Grid<Space>);
 
 impl State {
     fn advance(&mut self) -> bool {
-        let moved_east = self.advance_east_facing();
-        let moved_south = self.advance_south_facing();
+        let moved_east = self.advance_herd(Space::EastFacing, [0, 1]);
+        let moved_south = self.advance_herd(Space::SouthFacing, [1, 0]);
         moved_east || moved_south
     }
 
-    fn advance_east_facing(&mut self) -> bool {
-        let mut locations_to_move = HashSet::<[usize; 2]>::new();
-        for i in 0..self.0.nrows() {
-            for j in 0..self.0.ncols() {
-                let next_j = (j + 1) % self.0.ncols();
-                if self.0[[i, j]] == Space::EastFacing && self.0[[i, next_j]] == Space::Empty {
-                    locations_to_move.insert([i, j]);
-                }
-            }
-        }
-        for &[i, j] in &locations_to_move {
-            let next_j = (j + 1) % self.0.ncols();
-            self.0[[i, j]] = Space::Empty;
-            self.0[[i, next_j]] = Space::EastFacing;
-        }
-        !locations_to_move.is_empty()
-    }
-
-    fn advance_south_facing(&mut self) -> bool {
-        let mut locations_to_move = HashSet::<[usize; 2]>::new();
-        for i in 0..self.0.nrows() {
-            for j in 0..self.0.ncols() {
-                let next_i = (i + 1) % self.0.nrows();
-                if self.0[[i, j]] == Space::SouthFacing && self.0[[next_i, j]] == Space::Empty {
-                    locations_to_move.insert([i, j]);
-                }
-            }
-        }
-        for &[i, j] in &locations_to_move {
-            let next_i = (i + 1) % self.0.nrows();
-            self.0[[i, j]] = Space::Empty;
-            self.0[[next_i, j]] = Space::SouthFacing;
+    fn advance_herd(&mut self, herd: Space, offset: Offset) -> bool {
+        let grid = &mut self.0;
+        let locations_to_move = grid
+            .indices()
+            .filter(|&ij| {
+                grid[ij] == herd && grid[grid.wrapping_offset(ij, offset)] == Space::Empty
+            })
+            .collect::<Vec<_>>();
+        for &ij in &locations_to_move {
+            let next_ij = grid.wrapping_offset(ij, offset);
+            grid[ij] = Space::Empty;
+            grid[next_ij] = herd;
         }
         !locations_to_move.is_empty()
     }
@@ -1,19 +1,22 @@
 use crate::harness::input::RawInput;
-use ndarray::{Array1, Array2};
+use crate::util::bigint::BigUint;
+use crate::util::matrix::{self, Ordinary};
+use crate::util::nums::Arithmetic;
+use ndarray::Array1;
 
-pub fn solve_part1(input: RawInput) -> i64 {
+pub fn solve_part1(input: RawInput) -> BigUint {
     solve(input, 80)
 }
 
-pub fn solve_part2(input: RawInput) -> i64 {
+pub fn solve_part2(input: RawInput) -> BigUint {
     solve(input, 256)
 }
 
-fn solve(input: RawInput, days: usize) -> i64 {
+fn solve<T: Arithmetic>(input: RawInput, days: usize) -> T {
     let ages = input.single_line(|line| line.split::<usize>(","));
-    let mut age_counts = Array1::<i64>::zeros((9,));
+    let mut age_counts = Array1::<T>::default(9);
     for age in ages {
-        age_counts[[age]] += 1;
+        age_counts[[age]] += 1.into();
     }
     let one_day_transform = ndarray::arr2(&[
         [0, 1, 0, 0, 0, 0, 0, 0, 0],
@@ -25,21 +28,33 @@ fn solve(input: RawInput, days: usize) -> i64 {
         [1, 0, 0, 0, 0, 0, 0, 1, 0],
         [0, 0, 0, 0, 0, 0, 0, 0, 1],
         [1, 0, 0, 0, 0, 0, 0, 0, 0],
-    ]);
-    let n_days_transform = matrix_pow(one_day_transform, days);
-    n_days_transform.dot(&age_counts).sum()
+    ])
+    .map(|&x: &u8| T::from(x));
+    let n_days_transform = matrix::pow(&Ordinary, &one_day_transform, days as u64);
+    matrix::multiply_vector(&Ordinary, &n_days_transform, &age_counts)
+        .into_iter()
+        .sum()
 }
 
-fn matrix_pow(mut matrix: Array2<i64>, mut n: usize) -> Array2<i64> {
-    let mut result = Array2::eye(matrix.nrows());
-    loop {
-        if n & 1 == 1 {
-            result = result.dot(&matrix);
-        }
-        n >>= 1;
-        if n == 0 {
-            return result;
-        }
-        matrix = matrix.dot(&matrix);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::matrix::Modular;
+    use crate::util::recurrence;
+
+    #[test]
+    fn test_exact_at_large_scale() {
+        let input = RawInput::new("3,4,3,1,2");
+        assert_eq!(solve::<BigUint>(input, 256).to_string(), "26984457539");
+        // Well beyond `u128`, at 1260 bits.
+        let big = solve::<BigUint>(input, 10_000);
+        assert!(big.bits() > 1000);
+        // Check the result modulo a prime against the total's recurrence.
+        let prime = 1_000_000_007_u64;
+        let prefix = (0..20)
+            .map(|days| solve::<u64>(input, days))
+            .collect::<Vec<_>>();
+        let expected = recurrence::extrapolate(&Modular(prime), &prefix, 10_000);
+        assert_eq!(big % BigUint::from(prime), BigUint::from(expected));
     }
 }
@@ -1,4 +1,5 @@
 use crate::harness::input::RawInput;
+use crate::util::rational::Rational;
 use std::cmp;
 
 pub fn solve_part1(input: RawInput) -> i32 {
@@ -10,10 +11,11 @@ pub fn solve_part1(input: RawInput) -> i32 {
 
 pub fn solve_part2(input: RawInput) -> i32 {
     let nums = input.single_line(|line| line.split::<i32>(","));
-    let mean = nums.iter().sum::<i32>() / nums.len() as i32;
+    // The best position is within a half of the mean.
+    let mean = Rational::new(nums.iter().sum::<i32>(), nums.len() as i32);
     cmp::min(
-        triangle_diff_sum(&nums, mean),
-        triangle_diff_sum(&nums, mean + 1),
+        triangle_diff_sum(&nums, mean.floor()),
+        triangle_diff_sum(&nums, mean.ceil()),
     )
 }
 
@@ -59,7 +59,7 @@ impl<'a> LineInput<'a> {
     where
         T: MatchTuple,
     {
-        re::parse_with_regex(re, &self.0).unwrap()
+        re::parse_with_regex(re, self.0).unwrap()
     }
 }
 
@@ -71,6 +71,13 @@ impl<'a> RawInput<'a> {
         Self(s)
     }
 
+    /// The puzzle text of a test input file, without the expected outputs
+    /// before it. Panics if the file is not in the harness's format.
+    pub fn from_test_input(s: &'a str) -> Self {
+        let test_input = super::TestInput::<String, String>::try_from(s).unwrap();
+        Self(test_input.text)
+    }
+
     pub fn as_str(&self) -> &'a str {
         self.0
     }
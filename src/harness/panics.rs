@@ -36,13 +36,14 @@ where
 }
 
 fn is_todo(error: &dyn Any) -> bool {
-    if let Some(&s) = error.downcast_ref::<&str>() {
-        s == "not yet implemented"
+    let message = if let Some(&s) = error.downcast_ref::<&str>() {
+        s
     } else if let Some(s) = error.downcast_ref::<String>() {
-        s.starts_with("not yet implemented: ")
+        s.as_str()
     } else {
-        false
-    }
+        return false;
+    };
+    message == "not yet implemented" || message.starts_with("not yet implemented: ")
 }
 
 #[cfg(test)]
@@ -1,7 +1,16 @@
+pub mod bigint;
 pub mod coords;
 pub mod grid;
+pub mod intervals;
 pub mod ints;
+pub mod matrix;
 pub mod nums;
+#[cfg(test)]
+pub mod random;
+pub mod rational;
 pub mod re;
+pub mod recurrence;
+pub mod render;
 pub mod search;
+pub mod sparse_grid;
 pub mod string_enum;
@@ -0,0 +1,579 @@
+use std::cmp::{self, Ordering};
+use std::error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::iter::{Product, Sum};
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+use std::str::FromStr;
+
+/// An arbitrary precision unsigned integer, for answers which outgrow `u128`.
+/// The digits live on the heap, so it is `Clone` but not `Copy`, and
+/// implements `Arithmetic` rather than `Num`. Subtracting a larger value
+/// panics instead of wrapping.
+#[derive(Clone, Default, Eq, Hash, PartialEq)]
+pub struct BigUint {
+    /// Little-endian base-`2^64` digits, without trailing zeros, so that each
+    /// value has one representation and zero has none.
+    limbs: Vec<u64>,
+}
+
+/// An arbitrary precision signed integer. Division truncates toward zero, as
+/// for the primitive integers.
+#[derive(Clone, Default, Eq, Hash, PartialEq)]
+pub struct BigInt {
+    /// Never set for zero, so that each value has one representation.
+    negative: bool,
+    magnitude: BigUint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseBigIntError(String);
+
+impl Display for ParseBigIntError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid big integer {:?}.", self.0)
+    }
+}
+
+impl error::Error for ParseBigIntError {}
+
+/// The largest power of ten which fits in a limb, for converting to and from
+/// decimal.
+const DECIMAL_CHUNK: u64 = 10_000_000_000_000_000_000;
+const DECIMAL_CHUNK_DIGITS: usize = 19;
+
+impl BigUint {
+    pub const ZERO: Self = Self { limbs: Vec::new() };
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// The number of bits needed to represent the value.
+    pub fn bits(&self) -> u32 {
+        match self.limbs.last() {
+            None => 0,
+            Some(last) => 64 * self.limbs.len() as u32 - last.leading_zeros(),
+        }
+    }
+
+    fn from_limbs(mut limbs: Vec<u64>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { limbs }
+    }
+
+    fn limb(&self, i: usize) -> u64 {
+        self.limbs.get(i).copied().unwrap_or(0)
+    }
+
+    fn bit(&self, i: u32) -> bool {
+        self.limb(i as usize / 64) >> (i % 64) & 1 == 1
+    }
+
+    /// Returns `None` if `rhs` is larger.
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        if *self < *rhs {
+            return None;
+        }
+        let mut limbs = self.limbs.clone();
+        let mut borrow = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (difference, borrow1) = limb.overflowing_sub(rhs.limb(i));
+            let (difference, borrow2) = difference.overflowing_sub(borrow as u64);
+            *limb = difference;
+            borrow = borrow1 || borrow2;
+        }
+        Some(Self::from_limbs(limbs))
+    }
+
+    /// Returns the quotient and remainder. Panics if `rhs` is zero.
+    pub fn div_rem(&self, rhs: &Self) -> (Self, Self) {
+        assert!(!rhs.is_zero(), "Division of {} by zero.", self);
+        if let [divisor] = rhs.limbs[..] {
+            let (quotient, remainder) = self.div_rem_small(divisor);
+            return (quotient, Self::from(remainder));
+        }
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut remainder = Self::ZERO;
+        for i in (0..self.bits()).rev() {
+            remainder.shl1(self.bit(i));
+            if remainder >= *rhs {
+                remainder = remainder.checked_sub(rhs).unwrap();
+                quotient[i as usize / 64] |= 1 << (i % 64);
+            }
+        }
+        (Self::from_limbs(quotient), remainder)
+    }
+
+    fn div_rem_small(&self, rhs: u64) -> (Self, u64) {
+        let mut limbs = vec![0; self.limbs.len()];
+        let mut remainder = 0;
+        for i in (0..self.limbs.len()).rev() {
+            let dividend = (remainder as u128) << 64 | self.limbs[i] as u128;
+            limbs[i] = (dividend / rhs as u128) as u64;
+            remainder = (dividend % rhs as u128) as u64;
+        }
+        (Self::from_limbs(limbs), remainder)
+    }
+
+    /// Shifts left by one bit, shifting in `bit`.
+    fn shl1(&mut self, bit: bool) {
+        let mut carry = bit;
+        for limb in &mut self.limbs {
+            let next_carry = *limb >> 63 == 1;
+            *limb = *limb << 1 | carry as u64;
+            carry = next_carry;
+        }
+        if carry {
+            self.limbs.push(1);
+        }
+    }
+
+    /// Sets the value to `self * factor + addend`.
+    fn mul_add_small(&mut self, factor: u64, addend: u64) {
+        let mut carry = addend as u128;
+        for limb in &mut self.limbs {
+            let product = *limb as u128 * factor as u128 + carry;
+            *limb = product as u64;
+            carry = product >> 64;
+        }
+        self.limbs.push(carry as u64);
+        *self = Self::from_limbs(std::mem::take(&mut self.limbs));
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: &BigUint) -> BigUint {
+        let len = cmp::max(self.limbs.len(), rhs.limbs.len());
+        let mut limbs = Vec::with_capacity(len + 1);
+        let mut carry = false;
+        for i in 0..len {
+            let (sum, carry1) = self.limb(i).overflowing_add(rhs.limb(i));
+            let (sum, carry2) = sum.overflowing_add(carry as u64);
+            limbs.push(sum);
+            carry = carry1 || carry2;
+        }
+        limbs.push(carry as u64);
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Sub<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn sub(self, rhs: &BigUint) -> BigUint {
+        self.checked_sub(rhs)
+            .unwrap_or_else(|| panic!("Underflow in {} - {}.", self, rhs))
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: &BigUint) -> BigUint {
+        if self.is_zero() || rhs.is_zero() {
+            return BigUint::ZERO;
+        }
+        let mut limbs = vec![0; self.limbs.len() + rhs.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in rhs.limbs.iter().enumerate() {
+                let product = limbs[i + j] as u128 + a as u128 * b as u128 + carry;
+                limbs[i + j] = product as u64;
+                carry = product >> 64;
+            }
+            limbs[i + rhs.limbs.len()] = carry as u64;
+        }
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Div<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn div(self, rhs: &BigUint) -> BigUint {
+        self.div_rem(rhs).0
+    }
+}
+
+impl Rem<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn rem(self, rhs: &BigUint) -> BigUint {
+        self.div_rem(rhs).1
+    }
+}
+
+impl BigInt {
+    pub const ZERO: Self = Self {
+        negative: false,
+        magnitude: BigUint::ZERO,
+    };
+
+    pub fn new(negative: bool, magnitude: BigUint) -> Self {
+        Self {
+            negative: negative && !magnitude.is_zero(),
+            magnitude,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn magnitude(&self) -> &BigUint {
+        &self.magnitude
+    }
+
+    /// Returns the quotient, rounded toward zero, and the remainder, which
+    /// has the sign of `self`. Panics if `rhs` is zero.
+    pub fn div_rem(&self, rhs: &Self) -> (Self, Self) {
+        let (quotient, remainder) = self.magnitude.div_rem(&rhs.magnitude);
+        (
+            Self::new(self.negative != rhs.negative, quotient),
+            Self::new(self.negative, remainder),
+        )
+    }
+}
+
+impl Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, rhs: &BigInt) -> BigInt {
+        if self.negative == rhs.negative {
+            return BigInt::new(self.negative, &self.magnitude + &rhs.magnitude);
+        }
+        match self.magnitude.checked_sub(&rhs.magnitude) {
+            Some(magnitude) => BigInt::new(self.negative, magnitude),
+            None => BigInt::new(rhs.negative, &rhs.magnitude - &self.magnitude),
+        }
+    }
+}
+
+impl Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, rhs: &BigInt) -> BigInt {
+        self + &-rhs
+    }
+}
+
+impl Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, rhs: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != rhs.negative,
+            &self.magnitude * &rhs.magnitude,
+        )
+    }
+}
+
+impl Div<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn div(self, rhs: &BigInt) -> BigInt {
+        self.div_rem(rhs).0
+    }
+}
+
+impl Rem<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn rem(self, rhs: &BigInt) -> BigInt {
+        self.div_rem(rhs).1
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Neg for BigInt {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(!self.negative, self.magnitude)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (negative, _) => {
+                if negative {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(value: u128) -> Self {
+        Self::from_limbs(vec![value as u64, (value >> 64) as u64])
+    }
+}
+
+macro_rules! from_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for BigUint {
+                fn from(value: $t) -> Self {
+                    Self::from(value as u128)
+                }
+            }
+
+            impl From<$t> for BigInt {
+                fn from(value: $t) -> Self {
+                    Self::from(BigUint::from(value))
+                }
+            }
+        )*
+    };
+}
+
+from_unsigned!(u8, u16, u32, u64);
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        Self::new(value < 0, BigUint::from(value.unsigned_abs()))
+    }
+}
+
+impl From<BigUint> for BigInt {
+    fn from(magnitude: BigUint) -> Self {
+        Self::new(false, magnitude)
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut chunks = vec![];
+        let mut rest = self.clone();
+        while !rest.is_zero() {
+            let (quotient, chunk) = rest.div_rem_small(DECIMAL_CHUNK);
+            chunks.push(chunk);
+            rest = quotient;
+        }
+        let mut text = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.into_iter().rev() {
+            text += &format!("{:0width$}", chunk, width = DECIMAL_CHUNK_DIGITS);
+        }
+        f.pad_integral(true, "", &text)
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let text = self.magnitude.to_string();
+        f.pad_integral(!self.negative, "", &text)
+    }
+}
+
+impl Debug for BigUint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Debug for BigInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = ParseBigIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix('+').unwrap_or(s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigIntError(s.to_owned()));
+        }
+        let mut result = Self::ZERO;
+        for chunk in digits.as_bytes().chunks(DECIMAL_CHUNK_DIGITS) {
+            let scale = 10_u64.pow(chunk.len() as u32);
+            let value = chunk.iter().fold(0, |acc, b| acc * 10 + (b - b'0') as u64);
+            result.mul_add_small(scale, value);
+        }
+        Ok(result)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let magnitude = digits.parse().map_err(|_| ParseBigIntError(s.to_owned()))?;
+        Ok(Self::new(negative, magnitude))
+    }
+}
+
+/// Implements the by-value operators, which `Arithmetic` requires, in terms
+/// of those on references.
+macro_rules! owned_ops {
+    ($t:ty) => {
+        owned_ops! {
+            $t;
+            Add, add, AddAssign, add_assign;
+            Sub, sub, SubAssign, sub_assign;
+            Mul, mul, MulAssign, mul_assign;
+            Div, div, DivAssign, div_assign;
+            Rem, rem, RemAssign, rem_assign;
+        }
+
+        impl Sum for $t {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(<$t>::ZERO, |acc, x| &acc + &x)
+            }
+        }
+
+        impl Product for $t {
+            fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(<$t>::from(1_u8), |acc, x| &acc * &x)
+            }
+        }
+    };
+    ($t:ty; $($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident;)*) => {
+        $(
+            impl $trait for $t {
+                type Output = Self;
+
+                fn $method(self, rhs: Self) -> Self {
+                    $trait::$method(&self, &rhs)
+                }
+            }
+
+            impl $assign_trait for $t {
+                fn $assign_method(&mut self, rhs: Self) {
+                    *self = $trait::$method(&*self, &rhs);
+                }
+            }
+        )*
+    };
+}
+
+owned_ops!(BigUint);
+owned_ops!(BigInt);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::random::Random;
+
+    /// Values of every magnitude up to 64 bits, of either sign.
+    fn random_i64s(count: usize) -> impl Iterator<Item = i64> {
+        Random::new(0x9e37_79b9_7f4a_7c15)
+            .take(count)
+            .map(|x| x as i64 >> (x % 60))
+    }
+
+    fn big(value: i128) -> BigInt {
+        value.to_string().parse().unwrap()
+    }
+
+    #[test]
+    fn test_matches_i128() {
+        let values = random_i64s(60).chain([0, 1, -1]).collect::<Vec<_>>();
+        for &a in &values {
+            for &b in &values {
+                let (a, b) = (a as i128, b as i128);
+                assert_eq!(big(a) + big(b), big(a + b));
+                assert_eq!(big(a) - big(b), big(a - b));
+                assert_eq!(big(a) * big(b), big(a * b));
+                assert_eq!(big(a).cmp(&big(b)), a.cmp(&b));
+                if b != 0 {
+                    assert_eq!(big(a) / big(b), big(a / b));
+                    assert_eq!(big(a) % big(b), big(a % b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_display_and_parse() {
+        let factorial = (1..=30_u8).map(BigUint::from).product::<BigUint>();
+        assert_eq!(factorial.to_string(), "265252859812191058636308480000000");
+        assert_eq!(factorial.bits(), 108);
+        let text = "-123456789012345678901234567890123456789012345678901234567890";
+        let value = text.parse::<BigInt>().unwrap();
+        assert_eq!(value.to_string(), text);
+        assert_eq!(format!("{:>5}", BigInt::from(-12_i64)), "  -12");
+        assert_eq!("-0".parse::<BigInt>().unwrap(), BigInt::ZERO);
+        assert_eq!("000".parse::<BigUint>().unwrap(), BigUint::ZERO);
+        assert!("".parse::<BigUint>().is_err());
+        assert!("12a".parse::<BigUint>().is_err());
+    }
+
+    #[test]
+    fn test_large_division() {
+        let a = "987654321098765432109876543210987654321"
+            .parse::<BigUint>()
+            .unwrap();
+        let b = "123456789012345678901".parse::<BigUint>().unwrap();
+        let (quotient, remainder) = a.div_rem(&b);
+        assert_eq!(&(&quotient * &b) + &remainder, a);
+        assert!(remainder < b);
+        assert_eq!(quotient.to_string(), "8000000072900000663");
+        let max = BigUint::from(u128::MAX);
+        assert_eq!(&max / &BigUint::from(u128::MAX - 1), BigUint::from(1_u8));
+        assert_eq!(max % BigUint::from(u128::MAX - 1), BigUint::from(1_u8));
+    }
+
+    #[test]
+    fn test_grows() {
+        let two = BigUint::from(2_u8);
+        let power = (0..1000).map(|_| two.clone()).product::<BigUint>();
+        assert_eq!(power.bits(), 1001);
+        assert_eq!(power.to_string().len(), 302);
+        assert!(power.to_string().starts_with("10715086071862673209"));
+        let one = BigUint::from(1_u8);
+        assert_eq!(&(&power - &one) + &one, power);
+        assert_eq!(&power / &(&power - &one), one);
+    }
+
+    #[test]
+    #[should_panic(expected = "Underflow in 1 - 2.")]
+    fn test_underflow_panics() {
+        let _ = BigUint::from(1_u8) - BigUint::from(2_u8);
+    }
+}
@@ -1,6 +1,11 @@
 use crate::util::nums::Num;
 use derive_more::{Add, AddAssign, Neg, Product, Sub, SubAssign, Sum};
-use std::ops::{Div, DivAssign, Mul, MulAssign};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::iter::Sum;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
 
 #[derive(
     Add,
@@ -12,7 +17,9 @@ use std::ops::{Div, DivAssign, Mul, MulAssign};
     Eq,
     Hash,
     Neg,
+    Ord,
     PartialEq,
+    PartialOrd,
     Product,
     Sub,
     SubAssign,
@@ -30,7 +37,9 @@ pub struct Coord2<T: Num>(pub T, pub T);
     Eq,
     Hash,
     Neg,
+    Ord,
     PartialEq,
+    PartialOrd,
     Product,
     Sub,
     SubAssign,
@@ -48,7 +57,9 @@ pub struct Coord3<T: Num>(pub T, pub T, pub T);
     Eq,
     Hash,
     Neg,
+    Ord,
     PartialEq,
+    PartialOrd,
     Product,
     Sub,
     SubAssign,
@@ -56,6 +67,23 @@ pub struct Coord3<T: Num>(pub T, pub T, pub T);
 )]
 pub struct Coord4<T: Num>(pub T, pub T, pub T, pub T);
 
+/// A coordinate with any number of components, for code which handles several
+/// dimensionalities at once. Converts to and from the fixed-size types.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct CoordN<T: Num, const N: usize>(pub [T; N]);
+
+/// Accesses the components of a coordinate type by axis index, for code which
+/// is generic over the number of dimensions.
+pub trait Coord: Copy + Debug + Hash + PartialEq {
+    type Component: Num;
+
+    const DIMENSIONS: usize;
+
+    fn component(self, axis: usize) -> Self::Component;
+
+    fn with_component(self, axis: usize, value: Self::Component) -> Self;
+}
+
 macro_rules! coord_impls {
     ($name:ident, $($field:tt),*) => {
         impl<T: Num> $name<T> {
@@ -66,6 +94,90 @@ macro_rules! coord_impls {
             pub fn manhattan_norm(self) -> T {
                 T::default() $(+ self.$field.abs())*
             }
+
+            /// The largest absolute value of any component.
+            pub fn chebyshev_norm(self) -> T {
+                let mut result = T::default();
+                $(
+                    if self.$field.abs() > result {
+                        result = self.$field.abs();
+                    }
+                )*
+                result
+            }
+
+            pub fn squared_euclidean_norm(self) -> T {
+                self.dot(self)
+            }
+
+            pub fn dot(self, other: Self) -> T {
+                T::default() $(+ self.$field * other.$field)*
+            }
+
+            pub fn abs(self) -> Self {
+                Self($(self.$field.abs()),*)
+            }
+
+            pub fn signum(self) -> Self {
+                Self($(self.$field.signum()),*)
+            }
+
+            pub fn component_min(self, other: Self) -> Self {
+                Self($(if other.$field < self.$field { other.$field } else { self.$field }),*)
+            }
+
+            pub fn component_max(self, other: Self) -> Self {
+                Self($(if other.$field > self.$field { other.$field } else { self.$field }),*)
+            }
+
+            /// Whether every component lies between those of `min` and `max`,
+            /// inclusive.
+            pub fn is_within(self, min: Self, max: Self) -> bool {
+                true $(&& min.$field <= self.$field && self.$field <= max.$field)*
+            }
+
+            /// Returns the inclusive `(min, max)` corners of the smallest box
+            /// containing every coordinate, or `None` if there are none.
+            pub fn bounding_box(coords: impl IntoIterator<Item = Self>) -> Option<(Self, Self)> {
+                let mut coords = coords.into_iter();
+                let first = coords.next()?;
+                Some(coords.fold((first, first), |(min, max), coord| {
+                    (min.component_min(coord), max.component_max(coord))
+                }))
+            }
+        }
+
+        impl<T: Num> Coord for $name<T> {
+            type Component = T;
+
+            const DIMENSIONS: usize = [$(stringify!($field)),*].len();
+
+            fn component(self, axis: usize) -> T {
+                match axis {
+                    $($field => self.$field,)*
+                    _ => panic!("Axis {} out of range.", axis),
+                }
+            }
+
+            fn with_component(mut self, axis: usize, value: T) -> Self {
+                match axis {
+                    $($field => self.$field = value,)*
+                    _ => panic!("Axis {} out of range.", axis),
+                }
+                self
+            }
+        }
+
+        impl<T: Num> From<$name<T>> for CoordN<T, { [$(stringify!($field)),*].len() }> {
+            fn from(coord: $name<T>) -> Self {
+                Self([$(coord.$field),*])
+            }
+        }
+
+        impl<T: Num> From<CoordN<T, { [$(stringify!($field)),*].len() }>> for $name<T> {
+            fn from(coord: CoordN<T, { [$(stringify!($field)),*].len() }>) -> Self {
+                Self($(coord.0[$field]),*)
+            }
         }
 
         impl<T: Num> Mul<T> for $name<T> {
@@ -99,22 +211,25 @@ macro_rules! coord_impls {
 }
 
 macro_rules! neighbors_fn {
-    (@inner $self:ident $result:ident $neighbor:ident) => {
-        if $neighbor != $self {
-            $result.push($neighbor);
-        }
-    };
-    (@inner $self:ident $result:ident $neighbor:ident $head_field:tt $($rest_field:tt)*) => {
-        for x in [$self.$head_field - T::from(1), $self.$head_field, $self.$head_field + T::from(1)] {
-            $neighbor.$head_field = x;
-            neighbors_fn!(@inner $self $result $neighbor $($rest_field)*);
-        }
-    };
     ($($field:tt)*) => {
-        pub fn neighbors(self) -> Vec<Self> {
-            let mut result = vec![];
-            let mut neighbor = Self::default();
-            neighbors_fn!(@inner self result neighbor $($field)*);
+        /// Returns every coordinate which differs by at most one in each
+        /// component, excluding this one.
+        pub fn neighbors(self) -> [Self; 3usize.pow([$(stringify!($field)),*].len() as u32) - 1] {
+            let mut result = [self; 3usize.pow([$(stringify!($field)),*].len() as u32) - 1];
+            let center = result.len() / 2;
+            for (index, neighbor) in result.iter_mut().enumerate() {
+                let mut digits = if index < center { index } else { index + 1 };
+                for axis in 0..Self::DIMENSIONS {
+                    let x = self.component(axis);
+                    let x = match digits % 3 {
+                        0 => x - T::from(1),
+                        1 => x,
+                        _ => x + T::from(1),
+                    };
+                    *neighbor = neighbor.with_component(axis, x);
+                    digits /= 3;
+                }
+            }
             result
         }
     };
@@ -122,28 +237,334 @@ macro_rules! neighbors_fn {
 
 macro_rules! orthogonal_neighbors_fn {
     ($($field:tt)*) => {
-        pub fn orthogonal_neighbors(self) -> Vec<Self> {
-            let mut result = vec![];
-            $(
-                for x in [self.$field - T::from(1), self.$field + T::from(1)] {
-                    let mut neighbor = self;
-                    neighbor.$field = x;
-                    result.push(neighbor);
-                }
-            )*
+        /// Returns every coordinate which differs by one in a single component.
+        pub fn orthogonal_neighbors(self) -> [Self; 2 * [$(stringify!($field)),*].len()] {
+            let mut result = [self; 2 * [$(stringify!($field)),*].len()];
+            for axis in 0..Self::DIMENSIONS {
+                let x = self.component(axis);
+                result[2 * axis] = self.with_component(axis, x - T::from(1));
+                result[2 * axis + 1] = self.with_component(axis, x + T::from(1));
+            }
             result
         }
     };
 }
 
+impl<T: Num> Coord3<T> {
+    pub fn cross(self, other: Self) -> Self {
+        Coord3(
+            self.1 * other.2 - self.2 * other.1,
+            self.2 * other.0 - self.0 * other.2,
+            self.0 * other.1 - self.1 * other.0,
+        )
+    }
+}
+
+/// A rotation of 3D space which takes each axis onto an axis, or equivalently
+/// one of the 24 ways to orient a cube. Component `i` of a rotated coordinate
+/// is component `axes[i]` of the original, negated if `signs[i]` is negative.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Rotation {
+    axes: [usize; 3],
+    signs: [i8; 3],
+}
+
+impl Rotation {
+    pub const IDENTITY: Self = Self {
+        axes: [0, 1, 2],
+        signs: [1, 1, 1],
+    };
+
+    /// Every proper rotation, starting with the identity. These are the signed
+    /// permutations of the axes with determinant 1.
+    pub fn all() -> [Self; 24] {
+        let mut result = [Self::IDENTITY; 24];
+        let mut count = 0;
+        for axes in [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ] {
+            for signs in 0..8 {
+                let signs = [0, 1, 2].map(|i| if signs & (1 << i) == 0 { 1 } else { -1 });
+                let rotation = Self { axes, signs };
+                if rotation.determinant() == 1 {
+                    result[count] = rotation;
+                    count += 1;
+                }
+            }
+        }
+        result
+    }
+
+    /// Creates a rotation from its matrix, if the matrix is a signed
+    /// permutation matrix with determinant 1.
+    pub fn from_matrix(matrix: [[i32; 3]; 3]) -> Option<Self> {
+        let mut axes = [0; 3];
+        let mut signs = [0; 3];
+        for (i, row) in matrix.iter().enumerate() {
+            let mut nonzero = row.iter().enumerate().filter(|&(_, &x)| x != 0);
+            let (axis, &sign) = nonzero.next()?;
+            if nonzero.next().is_some() || sign.abs() != 1 {
+                return None;
+            }
+            axes[i] = axis;
+            signs[i] = sign as i8;
+        }
+        let mut sorted_axes = axes;
+        sorted_axes.sort_unstable();
+        let rotation = Self { axes, signs };
+        if sorted_axes == [0, 1, 2] && rotation.determinant() == 1 {
+            Some(rotation)
+        } else {
+            None
+        }
+    }
+
+    /// The matrix `m` for which the rotation of column vector `v` is `m v`.
+    pub fn matrix(self) -> [[i32; 3]; 3] {
+        let mut result = [[0; 3]; 3];
+        for i in 0..3 {
+            result[i][self.axes[i]] = self.signs[i] as i32;
+        }
+        result
+    }
+
+    pub fn apply<T: Num>(self, coord: Coord3<T>) -> Coord3<T> {
+        let parts = [coord.0, coord.1, coord.2];
+        let [x, y, z] = [0, 1, 2].map(|i| {
+            let part = parts[self.axes[i]];
+            if self.signs[i] < 0 {
+                part.unsafe_negate()
+            } else {
+                part
+            }
+        });
+        Coord3(x, y, z)
+    }
+
+    /// Returns the rotation which applies `other` and then `self`.
+    pub fn compose(self, other: Self) -> Self {
+        Self {
+            axes: self.axes.map(|axis| other.axes[axis]),
+            signs: [0, 1, 2].map(|i| self.signs[i] * other.signs[self.axes[i]]),
+        }
+    }
+
+    pub fn inverse(self) -> Self {
+        let mut result = self;
+        for i in 0..3 {
+            result.axes[self.axes[i]] = i;
+            result.signs[self.axes[i]] = self.signs[i];
+        }
+        result
+    }
+
+    fn determinant(self) -> i32 {
+        let [a, b, c] = self.axes;
+        let inversions = (a > b) as i32 + (a > c) as i32 + (b > c) as i32;
+        let permutation_sign = if inversions % 2 == 0 { 1 } else { -1 };
+        permutation_sign * self.signs.iter().map(|&s| s as i32).product::<i32>()
+    }
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
 coord_impls!(Coord2, 0, 1);
 coord_impls!(Coord3, 0, 1, 2);
 coord_impls!(Coord4, 0, 1, 2, 3);
 
+impl<T: Num, const N: usize> CoordN<T, N> {
+    /// Returns every coordinate which differs by at most one in each
+    /// component, excluding this one.
+    pub fn neighbors(self) -> impl Iterator<Item = Self> {
+        let count = 3usize.pow(N as u32);
+        (0..count)
+            .filter(move |&index| index != count / 2)
+            .map(move |mut digits| {
+                let mut neighbor = self;
+                for x in &mut neighbor.0 {
+                    match digits % 3 {
+                        0 => *x -= T::from(1),
+                        1 => {}
+                        _ => *x += T::from(1),
+                    }
+                    digits /= 3;
+                }
+                neighbor
+            })
+    }
+
+    /// Returns every coordinate which differs by one in a single component.
+    pub fn orthogonal_neighbors(self) -> impl Iterator<Item = Self> {
+        (0..2 * N).map(move |index| {
+            let mut neighbor = self;
+            if index % 2 == 0 {
+                neighbor.0[index / 2] -= T::from(1);
+            } else {
+                neighbor.0[index / 2] += T::from(1);
+            }
+            neighbor
+        })
+    }
+
+    pub fn manhattan_norm(self) -> T {
+        self.0.iter().map(|x| x.abs()).sum()
+    }
+
+    /// The largest absolute value of any component.
+    pub fn chebyshev_norm(self) -> T {
+        self.0
+            .iter()
+            .map(|x| x.abs())
+            .fold(T::default(), |a, b| if b > a { b } else { a })
+    }
+
+    pub fn squared_euclidean_norm(self) -> T {
+        self.dot(self)
+    }
+
+    pub fn dot(self, other: Self) -> T {
+        self.0.iter().zip(other.0).map(|(&x, y)| x * y).sum()
+    }
+
+    pub fn abs(self) -> Self {
+        Self(self.0.map(|x| x.abs()))
+    }
+
+    pub fn signum(self) -> Self {
+        Self(self.0.map(|x| x.signum()))
+    }
+}
+
+impl<T: Num, const N: usize> Default for CoordN<T, N> {
+    fn default() -> Self {
+        Self([T::default(); N])
+    }
+}
+
+impl<T: Num, const N: usize> Coord for CoordN<T, N> {
+    type Component = T;
+
+    const DIMENSIONS: usize = N;
+
+    fn component(self, axis: usize) -> T {
+        self.0[axis]
+    }
+
+    fn with_component(mut self, axis: usize, value: T) -> Self {
+        self.0[axis] = value;
+        self
+    }
+}
+
+impl<T: Num, const N: usize> Index<usize> for CoordN<T, N> {
+    type Output = T;
+
+    fn index(&self, axis: usize) -> &T {
+        &self.0[axis]
+    }
+}
+
+impl<T: Num, const N: usize> IndexMut<usize> for CoordN<T, N> {
+    fn index_mut(&mut self, axis: usize) -> &mut T {
+        &mut self.0[axis]
+    }
+}
+
+impl<T: Num, const N: usize> Add for CoordN<T, N> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl<T: Num, const N: usize> AddAssign for CoordN<T, N> {
+    fn add_assign(&mut self, rhs: Self) {
+        for (x, y) in self.0.iter_mut().zip(rhs.0) {
+            *x += y;
+        }
+    }
+}
+
+impl<T: Num, const N: usize> Sub for CoordN<T, N> {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
+
+impl<T: Num, const N: usize> SubAssign for CoordN<T, N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for (x, y) in self.0.iter_mut().zip(rhs.0) {
+            *x -= y;
+        }
+    }
+}
+
+impl<T: Num + Neg<Output = T>, const N: usize> Neg for CoordN<T, N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.map(|x| -x))
+    }
+}
+
+impl<T: Num, const N: usize> Mul<T> for CoordN<T, N> {
+    type Output = Self;
+
+    fn mul(mut self, rhs: T) -> Self::Output {
+        self *= rhs;
+        self
+    }
+}
+
+impl<T: Num, const N: usize> MulAssign<T> for CoordN<T, N> {
+    fn mul_assign(&mut self, rhs: T) {
+        for x in &mut self.0 {
+            *x *= rhs;
+        }
+    }
+}
+
+impl<T: Num, const N: usize> Div<T> for CoordN<T, N> {
+    type Output = Self;
+
+    fn div(mut self, rhs: T) -> Self::Output {
+        self /= rhs;
+        self
+    }
+}
+
+impl<T: Num, const N: usize> DivAssign<T> for CoordN<T, N> {
+    fn div_assign(&mut self, rhs: T) {
+        for x in &mut self.0 {
+            *x /= rhs;
+        }
+    }
+}
+
+impl<T: Num, const N: usize> Sum for CoordN<T, N> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |acc, c| acc + c)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashSet;
+    use std::collections::{BTreeSet, HashSet};
 
     #[test]
     fn test_coord2() {
@@ -167,17 +588,171 @@ mod tests {
         assert_eq!(neighbors_set, neighbors_expected);
         let neighbors_set: HashSet<_> = c.orthogonal_neighbors().into_iter().collect();
         let neighbors_expected: HashSet<_> =
-            [Coord2(0, -3), Coord2(0, -1), Coord2(2, -3), Coord2(2, -1)]
+            [Coord2(0, -2), Coord2(2, -2), Coord2(1, -3), Coord2(1, -1)]
                 .into_iter()
                 .collect();
         assert_eq!(neighbors_set, neighbors_expected);
     }
 
+    #[test]
+    fn test_metrics() {
+        let a = Coord3(1, -5, 3);
+        let b = Coord3(2, 0, -4);
+        assert_eq!(a.chebyshev_norm(), 5);
+        assert_eq!(a.squared_euclidean_norm(), 35);
+        assert_eq!(a.dot(b), -10);
+        assert_eq!(a.cross(b), Coord3(20, 10, 10));
+        assert_eq!(a.cross(b).dot(a), 0);
+        assert_eq!(Coord3(1, 0, 0).cross(Coord3(0, 1, 0)), Coord3(0, 0, 1));
+        assert_eq!(a.abs(), Coord3(1, 5, 3));
+        assert_eq!(a.signum(), Coord3(1, -1, 1));
+        assert_eq!(b.signum(), Coord3(1, 0, -1));
+        assert_eq!(a.component_min(b), Coord3(1, -5, -4));
+        assert_eq!(a.component_max(b), Coord3(2, 0, 3));
+        assert!(Coord2(3, 4).is_within(Coord2(0, 4), Coord2(3, 10)));
+        assert!(!Coord2(3, 4).is_within(Coord2(0, 5), Coord2(3, 10)));
+        let n = CoordN::from(a);
+        assert_eq!(n.chebyshev_norm(), 5);
+        assert_eq!(n.squared_euclidean_norm(), 35);
+        assert_eq!(n.dot(CoordN::from(b)), -10);
+        assert_eq!(n.signum(), CoordN([1, -1, 1]));
+    }
+
+    #[test]
+    fn test_bounding_box_and_ord() {
+        let coords = [Coord2(3, -1), Coord2(-2, 4), Coord2(0, 0)];
+        assert_eq!(
+            Coord2::bounding_box(coords),
+            Some((Coord2(-2, -1), Coord2(3, 4)))
+        );
+        assert_eq!(Coord2::<i32>::bounding_box([]), None);
+        let sorted = coords.into_iter().collect::<BTreeSet<_>>();
+        assert_eq!(
+            sorted.into_iter().collect::<Vec<_>>(),
+            vec![Coord2(-2, 4), Coord2(0, 0), Coord2(3, -1)]
+        );
+    }
+
+    #[test]
+    fn test_rotations_form_a_group() {
+        let all = Rotation::all();
+        assert_eq!(all[0], Rotation::IDENTITY);
+        let set: HashSet<_> = all.into_iter().collect();
+        assert_eq!(set.len(), 24);
+        for a in all {
+            assert_eq!(a.compose(a.inverse()), Rotation::IDENTITY);
+            assert_eq!(a.inverse().compose(a), Rotation::IDENTITY);
+            assert_eq!(Rotation::from_matrix(a.matrix()), Some(a));
+            for b in all {
+                let ab = a.compose(b);
+                assert!(set.contains(&ab));
+                let v = Coord3(1, 20, 300);
+                assert_eq!(ab.apply(v), a.apply(b.apply(v)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotations_are_proper() {
+        let x = Coord3(1, 0, 0);
+        let y = Coord3(0, 1, 0);
+        let v = Coord3(3, -7, 11);
+        let mut images = HashSet::new();
+        for rotation in Rotation::all() {
+            let (rx, ry) = (rotation.apply(x), rotation.apply(y));
+            assert_eq!(rx.cross(ry), rotation.apply(x.cross(y)));
+            assert_eq!(
+                rotation.apply(v).squared_euclidean_norm(),
+                v.squared_euclidean_norm()
+            );
+            let m = rotation.matrix();
+            let mv = Coord3(
+                m[0][0] * v.0 + m[0][1] * v.1 + m[0][2] * v.2,
+                m[1][0] * v.0 + m[1][1] * v.1 + m[1][2] * v.2,
+                m[2][0] * v.0 + m[2][1] * v.1 + m[2][2] * v.2,
+            );
+            assert_eq!(rotation.apply(v), mv);
+            images.insert(rotation.apply(v));
+        }
+        assert_eq!(images.len(), 24);
+        assert_eq!(
+            Rotation::from_matrix([[-1, 0, 0], [0, 1, 0], [0, 0, 1]]),
+            None
+        );
+        assert_eq!(
+            Rotation::from_matrix([[1, 1, 0], [0, 1, 0], [0, 0, 1]]),
+            None
+        );
+        assert_eq!(
+            Rotation::from_matrix([[1, 0, 0], [1, 0, 0], [0, 0, 1]]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_coord_n() {
+        let c = CoordN([1, -2, 3, 0, 5]);
+        assert_eq!(c + c, CoordN([2, -4, 6, 0, 10]));
+        assert_eq!(c - c, CoordN::default());
+        assert_eq!(-c * 2, CoordN([-2, 4, -6, 0, -10]));
+        assert_eq!(c.manhattan_norm(), 11);
+        assert_eq!(c[2], 3);
+        assert_eq!([c, c, c].into_iter().sum::<CoordN<_, 5>>(), c * 3);
+        let neighbors: HashSet<_> = c.neighbors().collect();
+        assert_eq!(neighbors.len(), 242);
+        assert!(!neighbors.contains(&c));
+        assert_eq!(c.orthogonal_neighbors().count(), 10);
+        assert!(c
+            .orthogonal_neighbors()
+            .all(|n| (n - c).manhattan_norm() == 1));
+    }
+
+    #[test]
+    fn test_coord_n_conversions() {
+        let c = Coord3(1, -2, 3);
+        let n = CoordN::from(c);
+        assert_eq!(n, CoordN([1, -2, 3]));
+        assert_eq!(Coord3::from(n), c);
+        assert_eq!(Coord2::from(CoordN([4, 5])), Coord2(4, 5));
+        assert_eq!(CoordN::from(Coord4(1, 2, 3, 4)).manhattan_norm(), 10);
+        let expected: HashSet<_> = c.neighbors().into_iter().map(CoordN::from).collect();
+        assert_eq!(n.neighbors().collect::<HashSet<_>>(), expected);
+        let expected: HashSet<_> = c
+            .orthogonal_neighbors()
+            .into_iter()
+            .map(CoordN::from)
+            .collect();
+        assert_eq!(n.orthogonal_neighbors().collect::<HashSet<_>>(), expected);
+    }
+
+    #[test]
+    fn test_coord4() {
+        let c = Coord4(0, 0, 0, 0);
+        let neighbors: HashSet<_> = c.neighbors().into_iter().collect();
+        assert_eq!(neighbors.len(), 80);
+        assert!(!neighbors.contains(&c));
+        assert_eq!(c.orthogonal_neighbors().len(), 8);
+    }
+
     #[test]
     fn test_coord3() {
         let c = Coord3(1, -2, 3);
         assert_eq!(c + c, Coord3(2, -4, 6));
         assert_eq!(c * 10, Coord3(10, -20, 30));
-        assert_eq!(c.manhattan_norm(), 6)
+        assert_eq!(c.manhattan_norm(), 6);
+        let neighbors: HashSet<_> = c.neighbors().into_iter().collect();
+        assert_eq!(neighbors.len(), 26);
+        assert!(!neighbors.contains(&c));
+        assert!(neighbors
+            .iter()
+            .all(|&n| (n - c).0.abs() <= 1 && (n - c).1.abs() <= 1 && (n - c).2.abs() <= 1));
+        assert_eq!(c.orthogonal_neighbors().len(), 6);
+        assert!(c
+            .orthogonal_neighbors()
+            .iter()
+            .all(|&n| (n - c).manhattan_norm() == 1));
+        assert_eq!(Coord3::<i32>::DIMENSIONS, 3);
+        assert_eq!(c.component(2), 3);
+        assert_eq!(c.with_component(0, 5), Coord3(5, -2, 3));
     }
 }
@@ -1,13 +1,69 @@
 use crate::harness::input::{LineInput, RawInput};
 use crate::util::coords::Coord2;
-use ndarray::Array2;
-use std::fmt::Debug;
-use std::ops::{Index, IndexMut};
+use crate::util::render::Rendering;
+use ndarray::{s, Array2, ArrayView2, ArrayViewMut2};
+use std::cmp;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::ops::{Index, IndexMut, Range};
 use std::str::FromStr;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Grid<T>(pub Array2<T>);
 
+/// A `[row, column]` displacement between two cells.
+pub type Offset = [isize; 2];
+
+/// Which cells around a given cell count as its neighbors.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Shape<'a> {
+    /// The four cells sharing an edge.
+    VonNeumann,
+    /// The eight cells sharing an edge or a corner.
+    Moore,
+    /// The six neighbors in a hexagonal grid stored in axial coordinates,
+    /// where moving right along a row and then up a row is a single step.
+    Hex,
+    Custom(&'a [Offset]),
+}
+
+const VON_NEUMANN_OFFSETS: [Offset; 4] = [[-1, 0], [0, -1], [0, 1], [1, 0]];
+
+const MOORE_OFFSETS: [Offset; 8] = [
+    [-1, -1],
+    [-1, 0],
+    [-1, 1],
+    [0, -1],
+    [0, 1],
+    [1, -1],
+    [1, 0],
+    [1, 1],
+];
+
+const HEX_OFFSETS: [Offset; 6] = [[-1, 0], [-1, 1], [0, -1], [0, 1], [1, -1], [1, 0]];
+
+impl<'a> Shape<'a> {
+    pub fn offsets(self) -> &'a [Offset] {
+        match self {
+            Shape::VonNeumann => &VON_NEUMANN_OFFSETS,
+            Shape::Moore => &MOORE_OFFSETS,
+            Shape::Hex => &HEX_OFFSETS,
+            Shape::Custom(offsets) => offsets,
+        }
+    }
+}
+
+/// How positions beyond the edges of a grid are treated.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Topology<T> {
+    /// There are no cells beyond the edges.
+    Bounded,
+    /// Each edge wraps around to the opposite one.
+    Toroidal,
+    /// The grid extends forever, and every cell beyond the edges holds the
+    /// given value.
+    Infinite(T),
+}
+
 impl Grid<u32> {
     pub fn parse_digits(s: &str) -> Self {
         parse_grid(s, |line| line.digits())
@@ -59,32 +115,100 @@ impl<T> Grid<T> {
         self.0.ncols()
     }
 
-    pub fn neighbors(&self, [i, j]: [usize; 2]) -> impl Iterator<Item = [usize; 2]> {
-        let nrows = self.nrows();
-        let ncols = self.ncols();
-        Coord2(i as i32, j as i32)
-            .neighbors()
-            .into_iter()
-            .map(|Coord2(i, j)| [i as usize, j as usize])
-            .filter(move |&[i, j]| i < nrows && j < ncols)
+    pub fn neighbors(&self, index: [usize; 2]) -> impl Iterator<Item = [usize; 2]> {
+        self.bounded_neighbors(index, &MOORE_OFFSETS)
+    }
+
+    pub fn orthogonal_neighbors(&self, index: [usize; 2]) -> impl Iterator<Item = [usize; 2]> {
+        self.bounded_neighbors(index, &VON_NEUMANN_OFFSETS)
     }
 
-    pub fn orthogonal_neighbors(&self, [i, j]: [usize; 2]) -> impl Iterator<Item = [usize; 2]> {
+    fn bounded_neighbors(
+        &self,
+        [i, j]: [usize; 2],
+        offsets: &'static [Offset],
+    ) -> impl Iterator<Item = [usize; 2]> {
         let nrows = self.nrows();
         let ncols = self.ncols();
-        Coord2(i as i32, j as i32)
-            .orthogonal_neighbors()
-            .into_iter()
-            .map(|Coord2(i, j)| [i as usize, j as usize])
+        offsets
+            .iter()
+            .map(move |&[di, dj]| [i.wrapping_add_signed(di), j.wrapping_add_signed(dj)])
             .filter(move |&[i, j]| i < nrows && j < ncols)
     }
 
+    /// Finds the cell at a position which may lie beyond the edges of the
+    /// grid. Returns the position it resolves to under `topology`, which only
+    /// differs for toroidal grids, along with its value.
+    pub fn resolve<'a>(
+        &'a self,
+        [i, j]: [isize; 2],
+        topology: &'a Topology<T>,
+    ) -> Option<([isize; 2], &'a T)> {
+        let nrows = self.nrows() as isize;
+        let ncols = self.ncols() as isize;
+        let is_inside = (0..nrows).contains(&i) && (0..ncols).contains(&j);
+        match topology {
+            Topology::Bounded if !is_inside => None,
+            Topology::Infinite(default) if !is_inside => Some(([i, j], default)),
+            Topology::Toroidal => {
+                let position = [i.rem_euclid(nrows), j.rem_euclid(ncols)];
+                Some((position, &self[position.map(|x| x as usize)]))
+            }
+            _ => Some(([i, j], &self[[i as usize, j as usize]])),
+        }
+    }
+
+    /// Iterates over the neighbors of a position, along with their values,
+    /// without allocating. For an infinite topology, the position and its
+    /// neighbors may lie beyond the edges of the grid.
+    pub fn neighborhood<'a>(
+        &'a self,
+        [i, j]: [isize; 2],
+        shape: Shape<'a>,
+        topology: &'a Topology<T>,
+    ) -> impl Iterator<Item = ([isize; 2], &'a T)> + 'a {
+        shape
+            .offsets()
+            .iter()
+            .filter_map(move |&[di, dj]| self.resolve([i + di, j + dj], topology))
+    }
+
+    /// Moves from `index` by `offset`, wrapping around the edges of the grid.
+    pub fn wrapping_offset(&self, [i, j]: [usize; 2], [di, dj]: Offset) -> [usize; 2] {
+        [
+            (i as isize + di).rem_euclid(self.nrows() as isize) as usize,
+            (j as isize + dj).rem_euclid(self.ncols() as isize) as usize,
+        ]
+    }
+
+    /// Like `neighbors`, but cells on an edge are adjacent to the cells on
+    /// the opposite edge.
+    pub fn wrapping_neighbors<'a>(
+        &'a self,
+        index: [usize; 2],
+        shape: Shape<'a>,
+    ) -> impl Iterator<Item = [usize; 2]> + 'a {
+        shape
+            .offsets()
+            .iter()
+            .map(move |&offset| self.wrapping_offset(index, offset))
+    }
+
     pub fn indices(&self) -> impl Iterator<Item = [usize; 2]> {
         let nrows = self.nrows();
         let ncols = self.ncols();
         (0..nrows).flat_map(move |i| (0..ncols).map(move |j| [i, j]))
     }
 
+    /// Borrows a rectangular region of the grid without copying it.
+    pub fn view(&self, rows: Range<usize>, cols: Range<usize>) -> ArrayView2<'_, T> {
+        self.0.slice(s![rows, cols])
+    }
+
+    pub fn view_mut(&mut self, rows: Range<usize>, cols: Range<usize>) -> ArrayViewMut2<'_, T> {
+        self.0.slice_mut(s![rows, cols])
+    }
+
     pub fn map<'a, U, F>(&'a self, f: F) -> Grid<U>
     where
         F: FnMut(&'a T) -> U,
@@ -92,6 +216,116 @@ impl<T> Grid<T> {
     {
         Grid(self.0.map(f))
     }
+
+    /// Renders each cell using `f`. Use `Rendering`'s methods to add rulers or
+    /// highlight cells, where index `[i, j]` is the point `(i, j)`.
+    pub fn render<D: Display>(&self, mut f: impl FnMut(&T) -> D) -> Rendering {
+        let max = Coord2(self.nrows() as i64 - 1, self.ncols() as i64 - 1);
+        Rendering::new(Coord2(0, 0), max, |Coord2(i, j)| {
+            f(&self[[i as usize, j as usize]])
+        })
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn transpose(&self) -> Self {
+        Grid(self.0.t().to_owned())
+    }
+
+    pub fn rotate_clockwise(&self) -> Self {
+        Grid(self.0.t().slice(s![.., ..;-1]).to_owned())
+    }
+
+    pub fn rotate_counterclockwise(&self) -> Self {
+        Grid(self.0.t().slice(s![..;-1, ..]).to_owned())
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        Grid(self.0.slice(s![..;-1, ..;-1]).to_owned())
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        Grid(self.0.slice(s![.., ..;-1]).to_owned())
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        Grid(self.0.slice(s![..;-1, ..]).to_owned())
+    }
+
+    /// Copies a rectangular region of the grid.
+    pub fn crop(&self, rows: Range<usize>, cols: Range<usize>) -> Self {
+        Grid(self.view(rows, cols).to_owned())
+    }
+
+    /// Repeats the grid `tile_rows` times downwards and `tile_cols` times
+    /// rightwards. Each value is passed through `f` along with the `[i, j]`
+    /// position of its tile.
+    pub fn tile<F>(&self, tile_rows: usize, tile_cols: usize, mut f: F) -> Self
+    where
+        F: FnMut(&T, [usize; 2]) -> T,
+    {
+        let (nrows, ncols) = (self.nrows(), self.ncols());
+        Grid(Array2::from_shape_fn(
+            (nrows * tile_rows, ncols * tile_cols),
+            |(i, j)| f(&self[[i % nrows, j % ncols]], [i / nrows, j / ncols]),
+        ))
+    }
+
+    /// Folds the part of the grid below `row` up over the part above it,
+    /// merging each pair of cells which land on each other with `f`. The fold
+    /// line itself is discarded. If the lower part is the taller one, the
+    /// result is padded at the top so that the fold line stays at the bottom.
+    /// Panics if `row` is not within the grid.
+    pub fn fold_up<F>(&self, row: usize, f: F) -> Self
+    where
+        F: FnMut(&T, &T) -> T,
+    {
+        Grid(fold_rows(self.0.view(), row, f))
+    }
+
+    /// Folds the part of the grid right of `col` over the part left of it, in
+    /// the same manner as `fold_up`.
+    pub fn fold_left<F>(&self, col: usize, f: F) -> Self
+    where
+        F: FnMut(&T, &T) -> T,
+    {
+        Grid(fold_rows(self.0.t(), col, f).reversed_axes())
+    }
+}
+
+fn fold_rows<T: Clone>(
+    array: ArrayView2<T>,
+    row: usize,
+    mut f: impl FnMut(&T, &T) -> T,
+) -> Array2<T> {
+    let nrows = array.nrows();
+    assert!(
+        row < nrows,
+        "Cannot fold along row {} of a grid with {} rows.",
+        row,
+        nrows
+    );
+    let height = cmp::max(row, nrows - row - 1);
+    Array2::from_shape_fn((height, array.ncols()), |(i, j)| {
+        let distance = height - i;
+        let above = row.checked_sub(distance).map(|i| &array[[i, j]]);
+        let below = Some(row + distance)
+            .filter(|&i| i < nrows)
+            .map(|i| &array[[i, j]]);
+        match (above, below) {
+            (Some(above), Some(below)) => f(above, below),
+            (Some(value), None) | (None, Some(value)) => value.clone(),
+            (None, None) => unreachable!(),
+        }
+    })
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(T::to_string))
+    }
 }
 
 fn parse_grid<T: Default>(input: &str, f: impl Fn(LineInput) -> Vec<T>) -> Grid<T> {
@@ -120,10 +354,147 @@ mod tests {
         assert_eq!(grid, expected);
     }
 
+    #[test]
+    fn test_display() {
+        let grid = Grid::parse_chars("#.\n.#");
+        assert_eq!(grid.to_string(), "#.\n.#");
+        let grid = Grid::<u32>::parse_on_whitespace("1 20\n300 4");
+        assert_eq!(grid.to_string(), "  1  20\n300   4");
+        let rendering = Grid::parse_digits("12\n34")
+            .render(|&d| d * 2)
+            .with_rulers();
+        assert_eq!(rendering.to_string(), "  01\n0 24\n1 68");
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid = Grid(ndarray::arr2(&[[1, 2, 3], [4, 5, 6]]));
+        assert_eq!(
+            grid.transpose(),
+            Grid(ndarray::arr2(&[[1, 4], [2, 5], [3, 6]]))
+        );
+        assert_eq!(
+            grid.rotate_clockwise(),
+            Grid(ndarray::arr2(&[[4, 1], [5, 2], [6, 3]]))
+        );
+        assert_eq!(
+            grid.rotate_counterclockwise(),
+            Grid(ndarray::arr2(&[[3, 6], [2, 5], [1, 4]]))
+        );
+        assert_eq!(
+            grid.rotate_180(),
+            Grid(ndarray::arr2(&[[6, 5, 4], [3, 2, 1]]))
+        );
+        assert_eq!(
+            grid.flip_horizontal(),
+            Grid(ndarray::arr2(&[[3, 2, 1], [6, 5, 4]]))
+        );
+        assert_eq!(
+            grid.flip_vertical(),
+            Grid(ndarray::arr2(&[[4, 5, 6], [1, 2, 3]]))
+        );
+        assert_eq!(
+            grid.rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise(),
+            grid.rotate_counterclockwise()
+        );
+    }
+
+    #[test]
+    fn test_views() {
+        let mut grid = Grid::parse_digits("123\n456\n789");
+        assert_eq!(
+            grid.crop(1..3, 0..2),
+            Grid(ndarray::arr2(&[[4, 5], [7, 8]]))
+        );
+        assert_eq!(grid.view(0..1, 1..3), ndarray::arr2(&[[2, 3]]));
+        grid.view_mut(0..2, 0..2).fill(0);
+        assert_eq!(grid, Grid::parse_digits("003\n006\n789"));
+    }
+
+    #[test]
+    fn test_tile() {
+        let grid = Grid::parse_digits("89");
+        let tiled = grid.tile(2, 2, |&risk, [i, j]| (risk + (i + j) as u32 - 1) % 9 + 1);
+        assert_eq!(tiled, Grid::parse_digits("8991\n9112"));
+    }
+
+    #[test]
+    fn test_fold() {
+        let grid = Grid::parse_chars("#..\n...\n...\n.#.\n..#");
+        let or = |&a: &char, &b: &char| if a == '#' || b == '#' { '#' } else { '.' };
+        assert_eq!(grid.fold_up(2, or), Grid::parse_chars("#.#\n.#."));
+        assert_eq!(grid.fold_up(1, or), Grid::parse_chars("..#\n.#.\n#.."));
+        assert_eq!(
+            grid.transpose().fold_left(2, or),
+            Grid::parse_chars("#.#\n.#.").transpose()
+        );
+        assert_eq!(grid.fold_up(4, or), Grid::parse_chars("#..\n...\n...\n.#."));
+        assert_eq!(
+            grid.fold_left(2, or),
+            Grid::parse_chars("#.\n..\n..\n.#\n..")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot fold along row 5 of a grid with 5 rows.")]
+    fn test_fold_past_edge() {
+        let grid = Grid::parse_chars("#..\n...\n...\n.#.\n..#");
+        grid.fold_up(5, |&a: &char, _| a);
+    }
+
+    #[test]
+    fn test_neighborhood() {
+        let grid = Grid::parse_digits("123\n456\n789");
+        let values = |shape, topology| {
+            let mut values = grid
+                .neighborhood([0, 0], shape, &topology)
+                .map(|(_, &value)| value)
+                .collect::<Vec<_>>();
+            values.sort();
+            values
+        };
+        assert_eq!(values(Shape::VonNeumann, Topology::Bounded), vec![2, 4]);
+        assert_eq!(values(Shape::Moore, Topology::Bounded), vec![2, 4, 5]);
+        assert_eq!(
+            values(Shape::VonNeumann, Topology::Toroidal),
+            vec![2, 3, 4, 7]
+        );
+        assert_eq!(
+            values(Shape::Moore, Topology::Toroidal),
+            vec![2, 3, 4, 5, 6, 7, 8, 9]
+        );
+        assert_eq!(
+            values(Shape::Moore, Topology::Infinite(0)),
+            vec![0, 0, 0, 0, 0, 2, 4, 5]
+        );
+        assert_eq!(values(Shape::Hex, Topology::Bounded), vec![2, 4]);
+        assert_eq!(
+            values(Shape::Custom(&[[2, 2], [-1, -1]]), Topology::Toroidal),
+            vec![9, 9]
+        );
+        let positions = grid
+            .neighborhood([-1, 5], Shape::VonNeumann, &Topology::Toroidal)
+            .map(|(position, _)| position)
+            .collect::<HashSet<_>>();
+        let expected = [[1, 2], [2, 1], [2, 0], [0, 2]].into_iter().collect();
+        assert_eq!(positions, expected);
+    }
+
+    #[test]
+    fn test_wrapping_neighbors() {
+        let grid = Grid(Array2::<i32>::zeros((3, 4)));
+        assert_eq!(grid.wrapping_offset([2, 3], [1, 1]), [0, 0]);
+        let neighbors: HashSet<_> = grid.wrapping_neighbors([0, 0], Shape::VonNeumann).collect();
+        let expected: HashSet<_> = [[2, 0], [1, 0], [0, 3], [0, 1]].into_iter().collect();
+        assert_eq!(neighbors, expected);
+    }
+
     #[test]
     fn test_neighbors() {
         let grid = Grid(Array2::<i32>::zeros((4, 4)));
-        let neighbors: HashSet<_> = grid.neighbors([1, 2]).into_iter().collect();
+        let neighbors: HashSet<_> = grid.neighbors([1, 2]).collect();
         let expected: HashSet<_> = [
             [0, 1],
             [1, 1],
@@ -143,6 +514,53 @@ mod tests {
         assert_eq!(neighbors, expected);
     }
 
+    /// Times `Grid::neighbors` against the `Vec`-based version it replaced.
+    /// Run with `cargo test --release bench_neighbors -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_neighbors() {
+        use std::hint::black_box;
+        use std::time::Instant;
+
+        // The previous `Coord2::neighbors` and `Grid::neighbors`.
+        fn vec_neighbors(c: Coord2<i32>) -> Vec<Coord2<i32>> {
+            let mut result = vec![];
+            for i in [c.0 - 1, c.0, c.0 + 1] {
+                for j in [c.1 - 1, c.1, c.1 + 1] {
+                    if Coord2(i, j) != c {
+                        result.push(Coord2(i, j));
+                    }
+                }
+            }
+            result
+        }
+        let grid = Grid(Array2::<u32>::ones((1000, 1000)));
+        let old_neighbors = |[i, j]: [usize; 2]| {
+            vec_neighbors(Coord2(i as i32, j as i32))
+                .into_iter()
+                .map(|Coord2(i, j)| [i as usize, j as usize])
+                .filter(|&[i, j]| i < grid.nrows() && j < grid.ncols())
+        };
+        for _ in 0..3 {
+            let start_time = Instant::now();
+            let old_sum: u32 = grid
+                .indices()
+                .flat_map(|ij| old_neighbors(black_box(ij)))
+                .map(|ij| grid[ij])
+                .sum();
+            let old_duration = start_time.elapsed();
+            let start_time = Instant::now();
+            let sum: u32 = grid
+                .indices()
+                .flat_map(|ij| grid.neighbors(black_box(ij)))
+                .map(|ij| grid[ij])
+                .sum();
+            let duration = start_time.elapsed();
+            assert_eq!(old_sum, sum);
+            println!("Vec: {:.2?}, offsets: {:.2?}", old_duration, duration);
+        }
+    }
+
     #[test]
     fn test_orthogonal_neighbors() {
         let grid = Grid(Array2::<i32>::zeros((4, 4)));
@@ -150,7 +568,7 @@ mod tests {
         let expected: HashSet<_> = [[0, 2], [2, 2], [1, 1], [1, 3]].into_iter().collect();
         assert_eq!(neighbors, expected);
 
-        let neighbors: HashSet<_> = grid.orthogonal_neighbors([0, 3]).into_iter().collect();
+        let neighbors: HashSet<_> = grid.orthogonal_neighbors([0, 3]).collect();
         let expected: HashSet<_> = [[1, 3], [0, 2]].into_iter().collect();
         assert_eq!(neighbors, expected);
     }
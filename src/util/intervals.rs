@@ -0,0 +1,455 @@
+use crate::util::coords::Coord;
+use crate::util::nums::{Int, Num};
+use std::cmp;
+
+/// The half-open interval `[start, end)`.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Int> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        Self { start, end }
+    }
+
+    /// Creates the interval `[start, end]`, which includes both endpoints.
+    pub fn inclusive(start: T, end: T) -> Self {
+        Self::new(start, end + 1.into())
+    }
+
+    pub fn len(self) -> T {
+        if self.is_empty() {
+            0.into()
+        } else {
+            self.end - self.start
+        }
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(self, x: T) -> bool {
+        self.start <= x && x < self.end
+    }
+
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        let result = Self::new(
+            cmp::max(self.start, other.start),
+            cmp::min(self.end, other.end),
+        );
+        if result.is_empty() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+}
+
+/// A set of integers stored as sorted, disjoint intervals. Intervals which
+/// touch are merged, so each set has exactly one representation.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IntervalSet<T>(Vec<Interval<T>>);
+
+impl<T: Int> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self(vec![])
+    }
+
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The number of integers in the set.
+    pub fn total_len(&self) -> T {
+        self.0.iter().map(|interval| interval.len()).sum()
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        let i = self.0.partition_point(|interval| interval.end <= x);
+        i < self.0.len() && self.0[i].contains(x)
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        *self = self.union(&Self::from(interval));
+    }
+
+    pub fn remove(&mut self, interval: Interval<T>) {
+        *self = self.difference(&Self::from(interval));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut all = [self.0.as_slice(), other.0.as_slice()].concat();
+        all.sort_by_key(|interval| interval.start);
+        let mut result = Vec::<Interval<T>>::new();
+        for interval in all {
+            match result.last_mut() {
+                Some(last) if interval.start <= last.end => {
+                    last.end = cmp::max(last.end, interval.end);
+                }
+                _ => result.push(interval),
+            }
+        }
+        Self(result)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.0.len() && j < other.0.len() {
+            let (a, b) = (self.0[i], other.0[j]);
+            if let Some(overlap) = a.intersection(b) {
+                result.push(overlap);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self(result)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = vec![];
+        let mut j = 0;
+        for &interval in &self.0 {
+            let mut start = interval.start;
+            while j < other.0.len() && other.0[j].end <= start {
+                j += 1;
+            }
+            for removed in other.0[j..]
+                .iter()
+                .take_while(|removed| removed.start < interval.end)
+            {
+                if start < removed.start {
+                    result.push(Interval::new(start, removed.start));
+                }
+                start = cmp::max(start, removed.end);
+            }
+            if start < interval.end {
+                result.push(Interval::new(start, interval.end));
+            }
+        }
+        Self(result)
+    }
+}
+
+impl<T: Int> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        if interval.is_empty() {
+            Self::new()
+        } else {
+            Self(vec![interval])
+        }
+    }
+}
+
+impl<T: Int> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let intervals = iter
+            .into_iter()
+            .filter(|interval| !interval.is_empty())
+            .collect();
+        Self::new().union(&Self(intervals))
+    }
+}
+
+/// The axis-aligned box containing the points `p` with `min <= p < max` on
+/// every axis.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Cuboid<C> {
+    pub min: C,
+    pub max: C,
+}
+
+impl<C> Cuboid<C>
+where
+    C: Coord,
+    C::Component: Int,
+{
+    pub fn new(min: C, max: C) -> Self {
+        Self { min, max }
+    }
+
+    /// Creates the box whose corners `min` and `max` are both included.
+    pub fn inclusive(min: C, mut max: C) -> Self {
+        for axis in 0..C::DIMENSIONS {
+            max = max.with_component(axis, max.component(axis) + 1.into());
+        }
+        Self::new(min, max)
+    }
+
+    pub fn axis(&self, axis: usize) -> Interval<C::Component> {
+        Interval::new(self.min.component(axis), self.max.component(axis))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..C::DIMENSIONS).any(|axis| self.axis(axis).is_empty())
+    }
+
+    /// The number of points in the box, computed in a type `V` which may be
+    /// wider than the coordinates to avoid overflow.
+    pub fn volume<V>(&self) -> V
+    where
+        V: Num + From<C::Component>,
+    {
+        if self.is_empty() {
+            return 0.into();
+        }
+        (0..C::DIMENSIONS)
+            .map(|axis| V::from(self.axis(axis).len()))
+            .product()
+    }
+
+    pub fn contains(&self, point: C) -> bool {
+        (0..C::DIMENSIONS).all(|axis| self.axis(axis).contains(point.component(axis)))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let mut result = *self;
+        for axis in 0..C::DIMENSIONS {
+            let overlap = self.axis(axis).intersection(other.axis(axis))?;
+            result.min = result.min.with_component(axis, overlap.start);
+            result.max = result.max.with_component(axis, overlap.end);
+        }
+        Some(result)
+    }
+
+    /// Returns disjoint boxes which together cover exactly the points of this
+    /// box that are not in `other`. At most two boxes are produced per axis.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let overlap = match self.intersection(other) {
+            Some(overlap) => overlap,
+            None => return vec![*self],
+        };
+        let mut result = vec![];
+        let mut remaining = *self;
+        for axis in 0..C::DIMENSIONS {
+            let kept = remaining.axis(axis);
+            let removed = overlap.axis(axis);
+            if kept.start < removed.start {
+                result.push(Self::new(
+                    remaining.min,
+                    remaining.max.with_component(axis, removed.start),
+                ));
+            }
+            if removed.end < kept.end {
+                result.push(Self::new(
+                    remaining.min.with_component(axis, removed.end),
+                    remaining.max,
+                ));
+            }
+            remaining.min = remaining.min.with_component(axis, removed.start);
+            remaining.max = remaining.max.with_component(axis, removed.end);
+        }
+        result
+    }
+}
+
+/// A set of points stored as a list of disjoint, non-empty boxes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CuboidSet<C>(Vec<Cuboid<C>>);
+
+impl<C> CuboidSet<C>
+where
+    C: Coord,
+    C::Component: Int,
+{
+    pub fn new() -> Self {
+        Self(vec![])
+    }
+
+    pub fn cuboids(&self) -> &[Cuboid<C>] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn volume<V>(&self) -> V
+    where
+        V: Num + From<C::Component>,
+    {
+        self.0.iter().map(|cuboid| cuboid.volume::<V>()).sum()
+    }
+
+    pub fn contains(&self, point: C) -> bool {
+        self.0.iter().any(|cuboid| cuboid.contains(point))
+    }
+
+    pub fn insert(&mut self, cuboid: Cuboid<C>) {
+        self.remove(cuboid);
+        if !cuboid.is_empty() {
+            self.0.push(cuboid);
+        }
+    }
+
+    pub fn remove(&mut self, cuboid: Cuboid<C>) {
+        self.0 = self
+            .0
+            .iter()
+            .flat_map(|existing| existing.subtract(&cuboid))
+            .collect();
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for &cuboid in &other.0 {
+            result.insert(cuboid);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self(
+            self.0
+                .iter()
+                .flat_map(|a| other.0.iter().filter_map(|b| a.intersection(b)))
+                .collect(),
+        )
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for &cuboid in &other.0 {
+            result.remove(cuboid);
+        }
+        result
+    }
+}
+
+impl<C> FromIterator<Cuboid<C>> for CuboidSet<C>
+where
+    C: Coord,
+    C::Component: Int,
+{
+    fn from_iter<I: IntoIterator<Item = Cuboid<C>>>(iter: I) -> Self {
+        let mut result = Self::new();
+        for cuboid in iter {
+            result.insert(cuboid);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::coords::{Coord2, Coord3};
+
+    fn interval_set(intervals: &[(i32, i32)]) -> IntervalSet<i32> {
+        intervals
+            .iter()
+            .map(|&(start, end)| Interval::new(start, end))
+            .collect()
+    }
+
+    #[test]
+    fn test_interval_set_normalizes() {
+        let set = interval_set(&[(5, 8), (0, 2), (2, 4), (7, 10), (12, 12)]);
+        assert_eq!(set, interval_set(&[(0, 4), (5, 10)]));
+        assert_eq!(set.total_len(), 9);
+        assert!(set.contains(3));
+        assert!(!set.contains(4));
+        assert!(set.contains(9));
+        assert!(!set.contains(10));
+    }
+
+    #[test]
+    fn test_interval_set_operations() {
+        let a = interval_set(&[(0, 10), (20, 30)]);
+        let b = interval_set(&[(5, 25), (28, 40)]);
+        assert_eq!(a.union(&b), interval_set(&[(0, 40)]));
+        assert_eq!(
+            a.intersection(&b),
+            interval_set(&[(5, 10), (20, 25), (28, 30)])
+        );
+        assert_eq!(a.difference(&b), interval_set(&[(0, 5), (25, 28)]));
+        assert_eq!(b.difference(&a), interval_set(&[(10, 20), (30, 40)]));
+        let mut c = a.clone();
+        c.remove(Interval::inclusive(2, 3));
+        c.insert(Interval::new(10, 12));
+        assert_eq!(c, interval_set(&[(0, 2), (4, 12), (20, 30)]));
+    }
+
+    #[test]
+    fn test_cuboid_subtract() {
+        let a = Cuboid::new(Coord3(0, 0, 0), Coord3(4, 4, 4));
+        let b = Cuboid::new(Coord3(1, 1, 1), Coord3(2, 3, 10));
+        let pieces = a.subtract(&b);
+        assert!(pieces.len() <= 6);
+        assert_eq!(
+            pieces.iter().map(|p| p.volume::<i64>()).sum::<i64>(),
+            64 - 6
+        );
+        for x in -1..5 {
+            for y in -1..5 {
+                for z in -1..5 {
+                    let p = Coord3(x, y, z);
+                    let count = pieces.iter().filter(|piece| piece.contains(p)).count();
+                    let expected = a.contains(p) && !b.contains(p);
+                    assert_eq!(count, expected as usize);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cuboid_set_reboot_steps() {
+        let steps = [
+            (true, Coord3(10, 10, 10), Coord3(12, 12, 12)),
+            (true, Coord3(11, 11, 11), Coord3(13, 13, 13)),
+            (false, Coord3(9, 9, 9), Coord3(11, 11, 11)),
+            (true, Coord3(10, 10, 10), Coord3(10, 10, 10)),
+        ];
+        let mut set = CuboidSet::new();
+        for (is_on, min, max) in steps {
+            let cuboid = Cuboid::inclusive(min, max);
+            if is_on {
+                set.insert(cuboid);
+            } else {
+                set.remove(cuboid);
+            }
+        }
+        assert_eq!(set.volume::<i64>(), 39);
+    }
+
+    #[test]
+    fn test_cuboid_set_volume_is_exact() {
+        let big = 1_000_000_000_i64;
+        let set: CuboidSet<_> = [
+            Cuboid::new(Coord3(-big, -big, -big), Coord3(big, big, big)),
+            Cuboid::new(Coord3(0, 0, 0), Coord3(2 * big, big, big)),
+        ]
+        .into_iter()
+        .collect();
+        let side = 2 * big as i128;
+        assert_eq!(
+            set.volume::<i128>(),
+            side * side * side + side * side * side / 8
+        );
+    }
+
+    #[test]
+    fn test_cuboid_set_operations() {
+        let a: CuboidSet<_> = [Cuboid::new(Coord2(0, 0), Coord2(4, 4))]
+            .into_iter()
+            .collect();
+        let b: CuboidSet<_> = [Cuboid::new(Coord2(2, 2), Coord2(6, 6))]
+            .into_iter()
+            .collect();
+        assert_eq!(a.union(&b).volume::<i32>(), 28);
+        assert_eq!(a.intersection(&b).volume::<i32>(), 4);
+        assert_eq!(a.difference(&b).volume::<i32>(), 12);
+        assert!(a.difference(&b).contains(Coord2(1, 3)));
+        assert!(!a.difference(&b).contains(Coord2(3, 3)));
+    }
+}
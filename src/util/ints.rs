@@ -1,8 +1,13 @@
-use crate::util::nums::Int;
+use crate::util::nums::{Int, SmallInt};
 
+/// The non-negative greatest common divisor. Unlike `extended_euclidean`,
+/// this never goes negative internally, so it works for unsigned types too.
 pub fn gcd<T: Int>(a: T, b: T) -> T {
-    let (x, y) = extended_euclidean(a, b);
-    a * x + b * y
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0.into() {
+        (a, b) = (b, a % b);
+    }
+    a
 }
 
 /// Returns the Bézout coefficients. That is, the returned (x, y) will satisfy
@@ -59,9 +64,212 @@ fn solve_congruence_pair<T: Int>((a, n): (T, T), (b, m): (T, T)) -> Option<(T, T
     Some(((a_quotient * m * y + b_quotient * n * x).modulus(lcm), lcm))
 }
 
+/// The smallest non-negative number divisible by both `a` and `b`.
+pub fn lcm<T: Int>(a: T, b: T) -> T {
+    if a == 0.into() || b == 0.into() {
+        return 0.into();
+    }
+    (a / gcd(a, b) * b).abs()
+}
+
+/// Returns `x` in `[0, |m|)` such that `ax = 1 (mod m)`, if `a` and `m` are
+/// coprime. The coefficients of `a` in the Euclidean algorithm alternate in
+/// sign, so only their magnitudes are tracked, which never exceed `|m|`.
+/// This works for unsigned types and for moduli near the type's limit.
+pub fn mod_inverse<T: Int>(a: T, m: T) -> Option<T> {
+    let m = m.abs();
+    let a = a % m;
+    let a = if a < 0.into() { a + m } else { a };
+    let mut rs = (m, a);
+    let mut coefficients: (T, T) = (0.into(), 1.into());
+    let mut is_negative = true;
+    while rs.1 != 0.into() {
+        let quotient = rs.0 / rs.1;
+        rs = (rs.1, rs.0 - quotient * rs.1);
+        coefficients = (coefficients.1, coefficients.0 + quotient * coefficients.1);
+        is_negative = !is_negative;
+    }
+    if rs.0 != 1.into() {
+        return None;
+    }
+    let x = coefficients.0 % m;
+    if is_negative && x != 0.into() {
+        Some(m - x)
+    } else {
+        Some(x)
+    }
+}
+
+/// Computes `(a + b) mod |m|` in `[0, |m|)` without overflowing. Restricted
+/// to `SmallInt` so that the sum can be taken in `u128`.
+pub fn addmod<T: SmallInt>(a: T, b: T, m: T) -> T {
+    let m = wide_modulus(m);
+    from_wide((reduce(a, m) + reduce(b, m)) % m)
+}
+
+/// Computes `ab mod |m|` in `[0, |m|)` without overflowing, whatever the
+/// signs and sizes of the inputs. Restricted to `SmallInt`, whose products
+/// fit in `u128`.
+pub fn mulmod<T: SmallInt>(a: T, b: T, m: T) -> T {
+    let m = wide_modulus(m);
+    from_wide(mulmod_wide(reduce(a, m), reduce(b, m), m))
+}
+
+/// Computes `base^exponent mod |m|` in `[0, |m|)` by repeated squaring, with
+/// `mulmod`, so also only for `SmallInt`.
+pub fn mod_pow<T: SmallInt>(base: T, exponent: T, m: T) -> T {
+    assert!(exponent >= 0.into(), "Negative exponent {}.", exponent);
+    let m = wide_modulus(m);
+    from_wide(mod_pow_wide(reduce(base, m), exponent.to_i128() as u128, m))
+}
+
+/// Uses Miller-Rabin, with witnesses that make it deterministic for every
+/// `SmallInt`. Wider types are not accepted.
+pub fn is_prime<T: SmallInt>(n: T) -> bool {
+    let n = n.to_i128();
+    n >= 2 && is_prime_wide(n as u128)
+}
+
+/// Returns the prime factors of `n` in increasing order, each with its
+/// multiplicity. Panics if `n` is not positive. Like `is_prime`, only for
+/// `SmallInt`.
+pub fn factorize<T: SmallInt>(n: T) -> Vec<(T, u32)> {
+    let n = n.to_i128();
+    assert!(n >= 1, "Cannot factorize {}.", n);
+    let mut n = n as u128;
+    let mut primes = vec![];
+    for d in 2..TRIAL_DIVISION_LIMIT {
+        while n.is_multiple_of(d) {
+            primes.push(d);
+            n /= d;
+        }
+    }
+    collect_large_prime_factors(n, &mut primes);
+    primes.sort_unstable();
+    let mut result = Vec::<(T, u32)>::new();
+    for prime in primes {
+        match result.last_mut() {
+            Some((last, count)) if *last == from_wide(prime) => *count += 1,
+            _ => result.push((from_wide(prime), 1)),
+        }
+    }
+    result
+}
+
+/// Witnesses for which Miller-Rabin is deterministic below `3.3 * 10^24`,
+/// well beyond any 64-bit input.
+const MILLER_RABIN_WITNESSES: [u128; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Factors below this are removed by trial division before Pollard's rho.
+const TRIAL_DIVISION_LIMIT: u128 = 100;
+
+fn wide_modulus<T: SmallInt>(m: T) -> u128 {
+    assert!(m != 0.into(), "Modulus is zero.");
+    m.to_i128().unsigned_abs()
+}
+
+fn reduce<T: SmallInt>(x: T, m: u128) -> u128 {
+    let x = x.to_i128();
+    if x >= 0 {
+        x as u128 % m
+    } else {
+        (m - x.unsigned_abs() % m) % m
+    }
+}
+
+fn from_wide<T: SmallInt>(x: u128) -> T {
+    T::from_i128(x as i128).unwrap_or_else(|| panic!("{} does not fit.", x))
+}
+
+/// Requires `a` and `b` to be less than `m`, which must be at most `2^64`.
+fn mulmod_wide(a: u128, b: u128, m: u128) -> u128 {
+    a * b % m
+}
+
+fn mod_pow_wide(mut base: u128, mut exponent: u128, m: u128) -> u128 {
+    let mut result = 1 % m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mulmod_wide(result, base, m);
+        }
+        base = mulmod_wide(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn is_prime_wide(n: u128) -> bool {
+    for &p in &MILLER_RABIN_WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    if n < 2 {
+        return false;
+    }
+    let shift = (n - 1).trailing_zeros();
+    let odd_part = (n - 1) >> shift;
+    MILLER_RABIN_WITNESSES.iter().all(|&witness| {
+        let mut x = mod_pow_wide(witness, odd_part, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..shift {
+            x = mulmod_wide(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Requires `n` to have no factors below `TRIAL_DIVISION_LIMIT`.
+fn collect_large_prime_factors(n: u128, primes: &mut Vec<u128>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime_wide(n) {
+        primes.push(n);
+        return;
+    }
+    let divisor = pollard_rho(n);
+    collect_large_prime_factors(divisor, primes);
+    collect_large_prime_factors(n / divisor, primes);
+}
+
+/// Finds a non-trivial divisor of the odd composite `n`.
+fn pollard_rho(n: u128) -> u128 {
+    for c in 1.. {
+        let step = |x| (mulmod_wide(x, x, n) + c) % n;
+        let (mut tortoise, mut hare) = (2, 2);
+        let mut divisor = 1;
+        while divisor == 1 {
+            tortoise = step(tortoise);
+            hare = step(step(hare));
+            divisor = gcd(tortoise.abs_diff(hare) as i128, n as i128) as u128;
+        }
+        if divisor != n {
+            return divisor;
+        }
+    }
+    unreachable!()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::util::nums::Num;
+    use crate::util::random::Random;
+
+    #[test]
+    fn test_wide_small_ints() {
+        assert_eq!(mulmod(u64::MAX - 1, u64::MAX - 1, u64::MAX), 1);
+        assert_eq!(mulmod(i64::MIN, i64::MIN, i64::MAX), 1);
+        assert_eq!(mod_pow(usize::MAX - 1, 3, usize::MAX), usize::MAX - 1);
+        assert!(is_prime(18_446_744_073_709_551_557_u64));
+        assert!(!is_prime(18_446_744_073_709_551_559_u64));
+    }
 
     #[test]
     fn test_gcd() {
@@ -69,6 +277,9 @@ mod test {
         assert_eq!(gcd(30, 25), 5);
         assert_eq!(gcd(1, 5), 1);
         assert_eq!(gcd(-3, 5), 1);
+        assert_eq!(gcd(-4, -6), 2);
+        assert_eq!(gcd(0_u64, 7), 7);
+        assert_eq!(gcd(12_u64, 18), 6);
     }
 
     #[test]
@@ -83,4 +294,147 @@ mod test {
     fn test_solve_congruences() {
         assert_eq!(solve_congruences(&[(0, 3), (3, 4), (4, 5)]), Some((39, 60)));
     }
+
+    fn random_u64s(count: usize) -> impl Iterator<Item = u64> {
+        Random::new(0x2545_f491_4f6c_dd1d).take(count)
+    }
+
+    fn sieve(limit: usize) -> Vec<bool> {
+        let mut is_prime = vec![true; limit];
+        is_prime[0] = false;
+        is_prime[1] = false;
+        for i in 2..limit {
+            if is_prime[i] {
+                for j in (i * i..limit).step_by(i) {
+                    is_prime[j] = false;
+                }
+            }
+        }
+        is_prime
+    }
+
+    #[test]
+    fn test_lcm() {
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(-4, 6), 12);
+        assert_eq!(lcm(0, 6), 0);
+        for a in -30..30_i64 {
+            for b in -30..30_i64 {
+                assert_eq!(lcm(a, b) * gcd(a, b), (a * b).abs());
+            }
+        }
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 7), Some(5));
+        assert_eq!(mod_inverse(-3, 7), Some(2));
+        assert_eq!(mod_inverse(4, 6), None);
+        assert_eq!(mod_inverse(3, -7), Some(5));
+        assert_eq!(mod_inverse(3_u64, 7), Some(5));
+        assert_eq!(mod_inverse(4_u64, 6), None);
+        assert_eq!(mod_inverse(5_u8, 1), Some(0));
+        assert_eq!(
+            mod_inverse(999_999_999_989_i64, 1_000_000_000_039),
+            Some(180_000_000_007)
+        );
+        assert_eq!(mod_inverse(u64::MAX - 1, u64::MAX), Some(u64::MAX - 1));
+        assert_eq!(mod_inverse(i64::MAX - 1, i64::MAX), Some(i64::MAX - 1));
+        for m in 1..60_i64 {
+            for a in -60..60_i64 {
+                match mod_inverse(a, m) {
+                    Some(x) => {
+                        assert!((0..m).contains(&x));
+                        assert_eq!((a * x).modulus(m), 1 % m);
+                    }
+                    None => assert_ne!(gcd(a, m), 1),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_addmod() {
+        assert_eq!(addmod(u64::MAX, u64::MAX, u64::MAX - 1), 2);
+        assert_eq!(addmod(i64::MIN, -1, 10), 1);
+        assert_eq!(addmod(-3, 1, -7), 5);
+    }
+
+    #[test]
+    fn test_mulmod() {
+        assert_eq!(mulmod(u64::MAX, u64::MAX, u64::MAX - 1), 1);
+        assert_eq!(mulmod(i64::MIN, i64::MAX, 1_000_000_007), {
+            let expected = (i64::MIN as i128 * i64::MAX as i128).rem_euclid(1_000_000_007);
+            expected as i64
+        });
+        assert_eq!(mulmod(-3, 5, -7), 6);
+        let values = random_u64s(300).collect::<Vec<_>>();
+        for chunk in values.chunks(3) {
+            let (a, b, m) = (chunk[0], chunk[1], chunk[2]);
+            let expected = (a as u128 * b as u128 % m as u128) as u64;
+            assert_eq!(mulmod(a, b, m), expected);
+        }
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        assert_eq!(mod_pow(2, 10, 1000), 24);
+        assert_eq!(mod_pow(5, 0, 1), 0);
+        assert_eq!(mod_pow(-2, 3, 5), 2);
+        for (base, m) in random_u64s(50).zip(random_u64s(51).skip(1)) {
+            let mut expected = 1 % m;
+            for exponent in 0..20_u64 {
+                assert_eq!(mod_pow(base, exponent, m), expected);
+                expected = mulmod(expected, base, m);
+            }
+        }
+        // Fermat's little theorem.
+        let p = 18_446_744_073_709_551_557_u64;
+        for a in random_u64s(20) {
+            assert_eq!(mod_pow(a % p, p - 1, p), if a % p == 0 { 0 } else { 1 });
+        }
+    }
+
+    #[test]
+    fn test_is_prime() {
+        let expected = sieve(10_000);
+        for (n, &expected) in expected.iter().enumerate() {
+            assert_eq!(is_prime(n as u64), expected, "{}", n);
+        }
+        assert!(!is_prime(-7));
+        assert!(is_prime(1_000_000_007));
+        assert!(is_prime(18_446_744_073_709_551_557_u64));
+        assert!(!is_prime(3_215_031_751_u64));
+        assert!(!is_prime(4_294_967_297_u64));
+    }
+
+    #[test]
+    fn test_factorize() {
+        assert_eq!(factorize(1), vec![]);
+        assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(
+            factorize(u64::MAX),
+            vec![
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65537, 1),
+                (6_700_417, 1)
+            ]
+        );
+        for n in random_u64s(50).map(|n| n >> 16).chain(1..1000) {
+            let factors = factorize(n);
+            let mut product = 1;
+            for window in factors.windows(2) {
+                assert!(window[0].0 < window[1].0);
+            }
+            for &(prime, count) in &factors {
+                assert!(is_prime(prime));
+                product *= prime.pow(count);
+            }
+            assert_eq!(product, n);
+        }
+    }
 }
@@ -0,0 +1,246 @@
+use crate::util::ints::{addmod, mulmod};
+use crate::util::nums::{Arithmetic, Num, SmallInt};
+use ndarray::{Array1, Array2};
+
+/// The addition and multiplication used when multiplying matrices of `T`.
+/// Addition must be associative and commutative with identity `zero`, and
+/// multiplication associative with identity `one`, distributing over addition.
+pub trait Semiring<T: Clone> {
+    fn zero(&self) -> T;
+    fn one(&self) -> T;
+    fn add(&self, a: T, b: T) -> T;
+    fn mul(&self, a: T, b: T) -> T;
+}
+
+/// Ordinary `(+, ×)` arithmetic.
+#[derive(Copy, Clone, Debug)]
+pub struct Ordinary;
+
+impl<T: Arithmetic> Semiring<T> for Ordinary {
+    fn zero(&self) -> T {
+        0.into()
+    }
+
+    fn one(&self) -> T {
+        1.into()
+    }
+
+    fn add(&self, a: T, b: T) -> T {
+        a + b
+    }
+
+    fn mul(&self, a: T, b: T) -> T {
+        a * b
+    }
+}
+
+/// Arithmetic modulo the given number, which never overflows.
+#[derive(Copy, Clone, Debug)]
+pub struct Modular<T>(pub T);
+
+impl<T: SmallInt> Semiring<T> for Modular<T> {
+    fn zero(&self) -> T {
+        0.into()
+    }
+
+    fn one(&self) -> T {
+        addmod(1.into(), 0.into(), self.0)
+    }
+
+    fn add(&self, a: T, b: T) -> T {
+        addmod(a, b, self.0)
+    }
+
+    fn mul(&self, a: T, b: T) -> T {
+        mulmod(a, b, self.0)
+    }
+}
+
+/// The tropical `(min, +)` semiring, in which `None` is infinity. The `n`th
+/// power of a matrix of edge weights gives the lightest walks of `n` edges.
+#[derive(Copy, Clone, Debug)]
+pub struct MinPlus;
+
+impl<T: Num> Semiring<Option<T>> for MinPlus {
+    fn zero(&self) -> Option<T> {
+        None
+    }
+
+    fn one(&self) -> Option<T> {
+        Some(0.into())
+    }
+
+    fn add(&self, a: Option<T>, b: Option<T>) -> Option<T> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(if b < a { b } else { a }),
+            _ => a.or(b),
+        }
+    }
+
+    fn mul(&self, a: Option<T>, b: Option<T>) -> Option<T> {
+        Some(a? + b?)
+    }
+}
+
+/// The `(max, +)` semiring, in which `None` is negative infinity. The `n`th
+/// power of a matrix of edge weights gives the heaviest walks of `n` edges.
+#[derive(Copy, Clone, Debug)]
+pub struct MaxPlus;
+
+impl<T: Num> Semiring<Option<T>> for MaxPlus {
+    fn zero(&self) -> Option<T> {
+        None
+    }
+
+    fn one(&self) -> Option<T> {
+        Some(0.into())
+    }
+
+    fn add(&self, a: Option<T>, b: Option<T>) -> Option<T> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(if b > a { b } else { a }),
+            _ => a.or(b),
+        }
+    }
+
+    fn mul(&self, a: Option<T>, b: Option<T>) -> Option<T> {
+        Some(a? + b?)
+    }
+}
+
+pub fn identity<T: Clone, S: Semiring<T>>(semiring: &S, n: usize) -> Array2<T> {
+    Array2::from_shape_fn((n, n), |(i, j)| {
+        if i == j {
+            semiring.one()
+        } else {
+            semiring.zero()
+        }
+    })
+}
+
+pub fn multiply<T: Clone, S: Semiring<T>>(semiring: &S, a: &Array2<T>, b: &Array2<T>) -> Array2<T> {
+    assert_eq!(a.ncols(), b.nrows(), "Incompatible matrix shapes.");
+    Array2::from_shape_fn((a.nrows(), b.ncols()), |(i, j)| {
+        (0..a.ncols()).fold(semiring.zero(), |acc, k| {
+            semiring.add(acc, semiring.mul(a[[i, k]].clone(), b[[k, j]].clone()))
+        })
+    })
+}
+
+pub fn multiply_vector<T: Clone, S: Semiring<T>>(
+    semiring: &S,
+    matrix: &Array2<T>,
+    vector: &Array1<T>,
+) -> Array1<T> {
+    assert_eq!(matrix.ncols(), vector.len(), "Incompatible shapes.");
+    Array1::from_shape_fn(matrix.nrows(), |i| {
+        (0..matrix.ncols()).fold(semiring.zero(), |acc, k| {
+            semiring.add(acc, semiring.mul(matrix[[i, k]].clone(), vector[k].clone()))
+        })
+    })
+}
+
+/// Raises a square matrix to the `n`th power by repeated squaring.
+pub fn pow<T: Clone, S: Semiring<T>>(semiring: &S, matrix: &Array2<T>, mut n: u64) -> Array2<T> {
+    assert_eq!(matrix.nrows(), matrix.ncols(), "Matrix is not square.");
+    let mut result = identity(semiring, matrix.nrows());
+    let mut power = matrix.clone();
+    while n > 0 {
+        if n & 1 == 1 {
+            result = multiply(semiring, &result, &power);
+        }
+        n >>= 1;
+        if n > 0 {
+            power = multiply(semiring, &power, &power);
+        }
+    }
+    result
+}
+
+/// Like `pow`, for a matrix stored as an array of rows.
+pub fn pow_array<T: Copy, S: Semiring<T>, const N: usize>(
+    semiring: &S,
+    matrix: &[[T; N]; N],
+    n: u64,
+) -> [[T; N]; N] {
+    let matrix = Array2::from_shape_fn((N, N), |(i, j)| matrix[i][j]);
+    let result = pow(semiring, &matrix, n);
+    let mut rows = [[semiring.zero(); N]; N];
+    for ((i, j), &value) in result.indexed_iter() {
+        rows[i][j] = value;
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordinary() {
+        let fibonacci = ndarray::arr2(&[[1_u64, 1], [1, 0]]);
+        assert_eq!(pow(&Ordinary, &fibonacci, 0), identity(&Ordinary, 2));
+        assert_eq!(
+            pow(&Ordinary, &fibonacci, 90)[[0, 1]],
+            2_880_067_194_370_816_120
+        );
+        let vector = ndarray::arr1(&[1_u64, 0]);
+        assert_eq!(
+            multiply_vector(&Ordinary, &pow(&Ordinary, &fibonacci, 10), &vector),
+            ndarray::arr1(&[89, 55])
+        );
+    }
+
+    #[test]
+    fn test_modular() {
+        let m = 1_000_000_007_u64;
+        let fibonacci = [[1, 1], [1, 0]];
+        let mut expected = (0, 1);
+        for n in 0..200 {
+            assert_eq!(pow_array(&Modular(m), &fibonacci, n)[0][1], expected.0);
+            expected = (expected.1, (expected.0 + expected.1) % m);
+        }
+        // The Pisano period of 10^9 + 7 divides 2 * (10^9 + 8).
+        let period = 2 * (m + 1);
+        assert_eq!(pow_array(&Modular(m), &fibonacci, period), [[1, 0], [0, 1]]);
+    }
+
+    #[test]
+    fn test_tropical() {
+        let weights = ndarray::arr2(&[
+            [None, Some(1), Some(5)],
+            [None, None, Some(1)],
+            [Some(2), None, None],
+        ]);
+        // Brute force over all walks of each length.
+        fn walks(weights: &Array2<Option<i32>>, length: u32) -> Vec<(usize, usize, i32)> {
+            let mut result = (0..3).map(|i| (i, i, 0)).collect::<Vec<_>>();
+            for _ in 0..length {
+                result = result
+                    .into_iter()
+                    .flat_map(|(start, end, weight)| {
+                        (0..3).filter_map(move |next| {
+                            Some((start, next, weight + weights[[end, next]]?))
+                        })
+                    })
+                    .collect();
+            }
+            result
+        }
+        for length in 0..8 {
+            let shortest = pow(&MinPlus, &weights, length as u64);
+            let longest = pow(&MaxPlus, &weights, length as u64);
+            let walks = walks(&weights, length);
+            for i in 0..3 {
+                for j in 0..3 {
+                    let weights = walks
+                        .iter()
+                        .filter(|&&(start, end, _)| start == i && end == j)
+                        .map(|&(_, _, weight)| weight);
+                    assert_eq!(shortest[[i, j]], weights.clone().min());
+                    assert_eq!(longest[[i, j]], weights.max());
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use std::fmt::{Debug, Display};
+use std::any::type_name;
+use std::fmt::{self, Debug, Display, Formatter};
 use std::hash::Hash;
 use std::iter::{Product, Sum};
 use std::ops::{
@@ -6,10 +7,11 @@ use std::ops::{
 };
 use std::str::FromStr;
 
-pub trait Num:
+/// The operations of `Num`, without requiring `Copy`, so that types owning
+/// heap memory such as `BigUint` can implement them.
+pub trait Arithmetic:
     Add<Output = Self>
     + AddAssign
-    + Copy
     + Clone
     + Debug
     + Display
@@ -30,33 +32,11 @@ pub trait Num:
     + SubAssign
     + Sum
 {
-    fn abs(self) -> Self {
-        if self >= Self::default() {
-            self
-        } else {
-            self.unsafe_negate()
-        }
-    }
-
-    /// Like `%` or `.rem()` except it always returns a non-negative result even
-    /// if one or both of the inputs are negative.
-    fn modulus(self, rhs: Self) -> Self {
-        let abs_rhs = rhs.abs();
-        ((self % abs_rhs) + abs_rhs) % abs_rhs
-    }
-
-    /// Negates the number if the number is signed, overflowing otherwise. This may
-    /// be safely called on a type `T` if we have observed a negative value of type
-    /// `T`.
-    fn unsafe_negate(self) -> Self {
-        Self::default() - self
-    }
 }
 
-impl<T> Num for T where
+impl<T> Arithmetic for T where
     T: Add<Output = Self>
         + AddAssign
-        + Copy
         + Clone
         + Debug
         + Display
@@ -79,6 +59,43 @@ impl<T> Num for T where
 {
 }
 
+pub trait Num: Arithmetic + Copy {
+    fn abs(self) -> Self {
+        if self >= Self::default() {
+            self
+        } else {
+            self.unsafe_negate()
+        }
+    }
+
+    /// Returns `1`, `0` or `-1` according to the sign of the number.
+    fn signum(self) -> Self {
+        if self > Self::default() {
+            1.into()
+        } else if self < Self::default() {
+            Self::from(1).unsafe_negate()
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Like `%` or `.rem()` except it always returns a non-negative result even
+    /// if one or both of the inputs are negative.
+    fn modulus(self, rhs: Self) -> Self {
+        let abs_rhs = rhs.abs();
+        ((self % abs_rhs) + abs_rhs) % abs_rhs
+    }
+
+    /// Negates the number if the number is signed, overflowing otherwise. This may
+    /// be safely called on a type `T` if we have observed a negative value of type
+    /// `T`.
+    fn unsafe_negate(self) -> Self {
+        Self::default() - self
+    }
+}
+
+impl<T> Num for T where T: Arithmetic + Copy {}
+
 pub trait Signed: Num + Neg {}
 
 impl<T> Signed for T where T: Num + Neg {}
@@ -86,3 +103,278 @@ impl<T> Signed for T where T: Num + Neg {}
 pub trait Int: Num + Eq + Ord {}
 
 impl<T> Int for T where T: Num + Eq + Ord {}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An integer type of at most 64 bits, so that values convert losslessly to
+/// `i128` and the product of two of them fits in a `u128`. Sealed, so that
+/// it is only implemented for the primitive integers up to 64 bits.
+pub trait SmallInt: Int + sealed::Sealed {
+    fn to_i128(self) -> i128;
+
+    /// Returns `None` if the value does not fit.
+    fn from_i128(value: i128) -> Option<Self>;
+}
+
+macro_rules! small_int_impls {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl SmallInt for $t {
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+
+                fn from_i128(value: i128) -> Option<Self> {
+                    Self::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+small_int_impls!(u8, u16, u32, u64, usize, i16, i32, i64, isize);
+
+/// The primitive integers' checked arithmetic, which returns `None` on
+/// overflow or division by zero.
+pub trait CheckedOps: Sized {
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+}
+
+macro_rules! checked_ops_impls {
+    ($($t:ty),*) => {
+        $(
+            impl CheckedOps for $t {
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+
+                fn checked_div(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_div(self, rhs)
+                }
+
+                fn checked_rem(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_rem(self, rhs)
+                }
+
+                fn checked_neg(self) -> Option<Self> {
+                    <$t>::checked_neg(self)
+                }
+            }
+        )*
+    };
+}
+
+checked_ops_impls!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// An integer which panics with the operation and its operands whenever
+/// arithmetic on it overflows, even in release builds. Swapping it in for a
+/// primitive type shows where a solution overflows.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Checked<T>(pub T);
+
+impl<T: Int + CheckedOps> Checked<T> {
+    fn check(self, op: &str, rhs: Self, result: Option<T>) -> Self {
+        match result {
+            Some(value) => Checked(value),
+            None if rhs.0 == 0.into() && (op == "/" || op == "%") => {
+                panic!(
+                    "Division by zero in {} {} 0 for {}.",
+                    self.0,
+                    op,
+                    type_name::<T>()
+                )
+            }
+            None => panic!(
+                "Overflow in {} {} {} for {}.",
+                self.0,
+                op,
+                rhs.0,
+                type_name::<T>()
+            ),
+        }
+    }
+}
+
+impl<T: Display> Display for Checked<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: FromStr> FromStr for Checked<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Checked)
+    }
+}
+
+impl<T: From<u8>> From<u8> for Checked<T> {
+    fn from(value: u8) -> Self {
+        Checked(value.into())
+    }
+}
+
+impl<T: Int + CheckedOps> Neg for Checked<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        match self.0.checked_neg() {
+            Some(value) => Checked(value),
+            None => panic!("Overflow in -({}) for {}.", self.0, type_name::<T>()),
+        }
+    }
+}
+
+macro_rules! checked_binary_ops {
+    ($($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $checked:ident, $op:literal;)*) => {
+        $(
+            impl<T: Int + CheckedOps> $trait for Checked<T> {
+                type Output = Self;
+
+                fn $method(self, rhs: Self) -> Self {
+                    self.check($op, rhs, self.0.$checked(rhs.0))
+                }
+            }
+
+            impl<T: Int + CheckedOps> $assign_trait for Checked<T> {
+                fn $assign_method(&mut self, rhs: Self) {
+                    *self = $trait::$method(*self, rhs);
+                }
+            }
+        )*
+    };
+}
+
+checked_binary_ops! {
+    Add, add, AddAssign, add_assign, checked_add, "+";
+    Sub, sub, SubAssign, sub_assign, checked_sub, "-";
+    Mul, mul, MulAssign, mul_assign, checked_mul, "*";
+    Div, div, DivAssign, div_assign, checked_div, "/";
+    Rem, rem, RemAssign, rem_assign, checked_rem, "%";
+}
+
+impl<T: Int + CheckedOps> Sum for Checked<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Checked(T::default()), Add::add)
+    }
+}
+
+impl<T: Int + CheckedOps> Product for Checked<T> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Checked(1.into()), Mul::mul)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::coords::{Coord2, Coord3};
+    use crate::util::ints::{gcd, solve_congruences};
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let a = Checked(200_u8);
+        assert_eq!(a + Checked(55), Checked(255));
+        assert_eq!(a / Checked(3) % Checked(7), Checked(3));
+        assert_eq!(Checked(-5_i32).abs(), Checked(5));
+        assert_eq!(
+            [Checked(3_i64), Checked(-4)]
+                .into_iter()
+                .product::<Checked<_>>(),
+            Checked(-12)
+        );
+        assert_eq!("42".parse::<Checked<u16>>(), Ok(Checked(42)));
+        assert_eq!(Checked(7_i8).to_string(), "7");
+    }
+
+    #[test]
+    fn test_checked_with_utilities() {
+        assert_eq!(gcd(Checked(30_i64), Checked(25)), Checked(5));
+        let congruences = [(0, 3), (3, 4), (4, 5)].map(|(a, n)| (Checked(a), Checked(n)));
+        assert_eq!(
+            solve_congruences(&congruences),
+            Some((Checked(39_i64), Checked(60)))
+        );
+        let point = Coord2(Checked(3_i32), Checked(-4));
+        assert_eq!(point.manhattan_norm(), Checked(7));
+        let cube = Coord3(Checked(1_u32), Checked(2), Checked(3));
+        assert_eq!(cube + cube, Coord3(Checked(2), Checked(4), Checked(6)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow in 200 + 100 for u8.")]
+    fn test_checked_add_overflow() {
+        let _ = Checked(200_u8) + Checked(100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow in 1 - 2 for u32.")]
+    fn test_checked_sub_overflow() {
+        let _ = Checked(1_u32) - Checked(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow in -(-2147483648) for i32.")]
+    fn test_checked_neg_overflow() {
+        let _ = -Checked(i32::MIN);
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero in 1 / 0 for i64.")]
+    fn test_checked_division_by_zero() {
+        let _ = Checked(1_i64) / Checked(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero in 7 % 0 for u8.")]
+    fn test_checked_remainder_by_zero() {
+        let _ = Checked(7_u8) % Checked(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow in -32768 / -1 for i16.")]
+    fn test_checked_division_overflow() {
+        let _ = Checked(i16::MIN) / Checked(-1);
+    }
+
+    /// Resolves to `()` for any type, but becomes ambiguous, and so fails to
+    /// compile, for a type implementing `SmallInt`.
+    trait AmbiguousIfSmallInt<A> {
+        fn check() {}
+    }
+
+    impl<T> AmbiguousIfSmallInt<()> for T {}
+
+    struct IsSmallInt;
+
+    impl<T: SmallInt> AmbiguousIfSmallInt<IsSmallInt> for T {}
+
+    #[test]
+    fn test_small_int_types() {
+        // Compiles only while 128-bit types are not `SmallInt`, since their
+        // products would overflow the `u128` arithmetic in `ints`.
+        <i128 as AmbiguousIfSmallInt<_>>::check();
+        <u128 as AmbiguousIfSmallInt<_>>::check();
+        assert_eq!(usize::MAX.to_i128(), u64::MAX as i128);
+        assert_eq!(i16::from_i128(-32768), Some(i16::MIN));
+        assert_eq!(u8::from_i128(256), None);
+    }
+}
@@ -0,0 +1,34 @@
+/// A xorshift generator for property tests. It is seeded explicitly so that
+/// failures reproduce.
+#[derive(Clone, Debug)]
+pub struct Random(u64);
+
+impl Random {
+    /// Panics if `seed` is zero, since xorshift would only ever return zero.
+    pub fn new(seed: u64) -> Self {
+        assert!(seed != 0, "Seed must be non-zero.");
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A value in `[0, n)`, slightly biased towards small values unless `n`
+    /// is a power of two.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+/// An endless stream of values.
+impl Iterator for Random {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        Some(self.next_u64())
+    }
+}
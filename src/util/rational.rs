@@ -0,0 +1,312 @@
+use crate::util::ints::gcd;
+use crate::util::nums::Int;
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::iter::{Product, Sum};
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+use std::str::FromStr;
+
+/// An exact fraction, always stored in lowest terms with a positive
+/// denominator so that equal values compare and hash equal.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Rational<T> {
+    numer: T,
+    denom: T,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseRationalError(String);
+
+impl Display for ParseRationalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid rational {:?}.", self.0)
+    }
+}
+
+impl std::error::Error for ParseRationalError {}
+
+impl<T: Int> Rational<T> {
+    /// Panics if `denom` is zero.
+    pub fn new(numer: T, denom: T) -> Self {
+        assert!(
+            denom != 0.into(),
+            "Rational {}/0 has a zero denominator.",
+            numer
+        );
+        let divisor = gcd(numer, denom).abs();
+        let (numer, denom) = (numer / divisor, denom / divisor);
+        if denom < 0.into() {
+            Self {
+                numer: numer.unsafe_negate(),
+                denom: denom.unsafe_negate(),
+            }
+        } else {
+            Self { numer, denom }
+        }
+    }
+
+    pub fn from_integer(value: T) -> Self {
+        Self {
+            numer: value,
+            denom: 1.into(),
+        }
+    }
+
+    pub fn numer(&self) -> T {
+        self.numer
+    }
+
+    pub fn denom(&self) -> T {
+        self.denom
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1.into()
+    }
+
+    /// The largest integer no greater than the value.
+    pub fn floor(&self) -> T {
+        let quotient = self.numer / self.denom;
+        if quotient * self.denom > self.numer {
+            quotient - 1.into()
+        } else {
+            quotient
+        }
+    }
+
+    /// The smallest integer no less than the value.
+    pub fn ceil(&self) -> T {
+        let floor = self.floor();
+        if self.is_integer() {
+            floor
+        } else {
+            floor + 1.into()
+        }
+    }
+
+    /// The integer part of the value, rounding toward zero.
+    pub fn trunc(&self) -> T {
+        self.numer / self.denom
+    }
+
+    /// Panics if the value is zero.
+    pub fn recip(&self) -> Self {
+        Self::new(self.denom, self.numer)
+    }
+}
+
+impl<T: Int> Default for Rational<T> {
+    fn default() -> Self {
+        Self::from_integer(0.into())
+    }
+}
+
+impl<T: Int> From<u8> for Rational<T> {
+    fn from(value: u8) -> Self {
+        Self::from_integer(value.into())
+    }
+}
+
+impl<T: Int> Ord for Rational<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numer * other.denom).cmp(&(other.numer * self.denom))
+    }
+}
+
+impl<T: Int> PartialOrd for Rational<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Int> Display for Rational<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+/// Parses `"n/d"` or a plain integer `"n"`.
+impl<T: Int> FromStr for Rational<T> {
+    type Err = ParseRationalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseRationalError(s.to_owned());
+        let (numer, denom) = s.split_once('/').unwrap_or((s, "1"));
+        let numer = numer.trim().parse::<T>().map_err(|_| error())?;
+        let denom = denom.trim().parse::<T>().map_err(|_| error())?;
+        if denom == 0.into() {
+            return Err(error());
+        }
+        Ok(Self::new(numer, denom))
+    }
+}
+
+impl<T: Int> Add for Rational<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let denom = self.denom / gcd(self.denom, rhs.denom) * rhs.denom;
+        Self::new(
+            self.numer * (denom / self.denom) + rhs.numer * (denom / rhs.denom),
+            denom,
+        )
+    }
+}
+
+impl<T: Int> Sub for Rational<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let denom = self.denom / gcd(self.denom, rhs.denom) * rhs.denom;
+        Self::new(
+            self.numer * (denom / self.denom) - rhs.numer * (denom / rhs.denom),
+            denom,
+        )
+    }
+}
+
+impl<T: Int> Mul for Rational<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        // Cancel first to keep the intermediate products small.
+        let left = Self::new(self.numer, rhs.denom);
+        let right = Self::new(rhs.numer, self.denom);
+        Self::new(left.numer * right.numer, left.denom * right.denom)
+    }
+}
+
+impl<T: Int> Div for Rational<T> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Mul::mul(self, rhs.recip())
+    }
+}
+
+/// The remainder of truncating division, with the sign of `self`, as for the
+/// primitive integers.
+impl<T: Int> Rem for Rational<T> {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self {
+        self - rhs * Self::from_integer((self / rhs).trunc())
+    }
+}
+
+impl<T: Int + Neg<Output = T>> Neg for Rational<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            numer: -self.numer,
+            denom: self.denom,
+        }
+    }
+}
+
+macro_rules! assign_ops {
+    ($($assign_trait:ident, $assign_method:ident, $trait:ident, $method:ident;)*) => {
+        $(
+            impl<T: Int> $assign_trait for Rational<T> {
+                fn $assign_method(&mut self, rhs: Self) {
+                    *self = $trait::$method(*self, rhs);
+                }
+            }
+        )*
+    };
+}
+
+assign_ops! {
+    AddAssign, add_assign, Add, add;
+    SubAssign, sub_assign, Sub, sub;
+    MulAssign, mul_assign, Mul, mul;
+    DivAssign, div_assign, Div, div;
+    RemAssign, rem_assign, Rem, rem;
+}
+
+impl<T: Int> Sum for Rational<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl<T: Int> Product for Rational<T> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::from(1), Mul::mul)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::coords::{Coord2, Coord3};
+    use crate::util::nums::Num;
+
+    fn r(numer: i64, denom: i64) -> Rational<i64> {
+        Rational::new(numer, denom)
+    }
+
+    #[test]
+    fn test_normalization() {
+        assert_eq!(r(6, -8), r(-3, 4));
+        assert_eq!((r(6, -8).numer(), r(6, -8).denom()), (-3, 4));
+        assert_eq!(r(0, -5), Rational::default());
+        assert_eq!(r(0, -5).denom(), 1);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(r(1, 2) + r(1, 3), r(5, 6));
+        assert_eq!(r(1, 2) - r(3, 4), r(-1, 4));
+        assert_eq!(r(2, 3) * r(9, 4), r(3, 2));
+        assert_eq!(r(2, 3) / r(-4, 9), r(-3, 2));
+        assert_eq!(r(7, 2) % r(1, 1), r(1, 2));
+        assert_eq!(r(-7, 2) % r(1, 1), r(-1, 2));
+        assert_eq!(-r(1, 3), r(-1, 3));
+        assert_eq!(r(-7, 2).abs(), r(7, 2));
+        assert_eq!(r(-7, 2).signum(), r(-1, 1));
+        assert_eq!((1..=4).map(|n| r(1, n)).sum::<Rational<_>>(), r(25, 12));
+    }
+
+    #[test]
+    fn test_rounding_and_order() {
+        assert_eq!(
+            (r(7, 2).floor(), r(7, 2).ceil(), r(7, 2).trunc()),
+            (3, 4, 3)
+        );
+        assert_eq!(
+            (r(-7, 2).floor(), r(-7, 2).ceil(), r(-7, 2).trunc()),
+            (-4, -3, -3)
+        );
+        assert_eq!((r(4, 2).floor(), r(4, 2).ceil()), (2, 2));
+        assert!(r(1, 3) < r(1, 2));
+        assert!(r(-1, 2) < r(-1, 3));
+    }
+
+    #[test]
+    fn test_display_and_parse() {
+        assert_eq!(r(-3, 4).to_string(), "-3/4");
+        assert_eq!(r(8, 4).to_string(), "2");
+        assert_eq!("6/-8".parse(), Ok(r(-3, 4)));
+        assert_eq!("5".parse(), Ok(r(5, 1)));
+        assert!("1/0".parse::<Rational<i64>>().is_err());
+        assert!("x/2".parse::<Rational<i64>>().is_err());
+    }
+
+    #[test]
+    fn test_coords() {
+        let direction = Coord2(r(3, 1), r(1, 1));
+        let t = r(1, 4);
+        assert_eq!(direction * t, Coord2(r(3, 4), r(1, 4)));
+        let cube = Coord3(r(1, 2), r(1, 3), r(1, 6));
+        assert_eq!(cube.manhattan_norm(), r(1, 1));
+        assert_eq!(cube - cube, Coord3::default());
+    }
+}
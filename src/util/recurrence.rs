@@ -0,0 +1,220 @@
+use crate::util::ints::{is_prime, mod_inverse, mulmod};
+use crate::util::matrix::{self, Modular, Ordinary, Semiring};
+use crate::util::nums::{Int, SmallInt};
+use crate::util::rational::Rational;
+use ndarray::{Array1, Array2};
+
+/// A semiring in which every element has an additive inverse and every
+/// non-zero element a multiplicative one.
+pub trait Field<T: Copy>: Semiring<T> {
+    fn neg(&self, a: T) -> T;
+    fn inv(&self, a: T) -> T;
+
+    /// Panics if the arithmetic is not actually a field.
+    fn validate(&self) {}
+}
+
+/// Integers modulo a prime. Only a prime modulus gives a field, so
+/// validation panics if the modulus is composite rather than letting
+/// Berlekamp-Massey return a wrong recurrence.
+impl<T: SmallInt> Field<T> for Modular<T> {
+    fn neg(&self, a: T) -> T {
+        let m = self.0.abs();
+        mulmod(a, m - 1.into(), m)
+    }
+
+    fn inv(&self, a: T) -> T {
+        mod_inverse(a, self.0).expect("Zero has no inverse.")
+    }
+
+    fn validate(&self) {
+        let m = self.0.abs();
+        assert!(is_prime(m), "Modulus {} is not prime.", m);
+    }
+}
+
+/// Exact rational arithmetic.
+impl<T: Int> Field<Rational<T>> for Ordinary {
+    fn neg(&self, a: Rational<T>) -> Rational<T> {
+        Rational::default() - a
+    }
+
+    fn inv(&self, a: Rational<T>) -> Rational<T> {
+        a.recip()
+    }
+}
+
+/// A sequence satisfying
+/// > s(n) = c(0) s(n - 1) + c(1) s(n - 2) + ... + c(k - 1) s(n - k)
+///
+/// for every `n >= k`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearRecurrence<T> {
+    pub coefficients: Vec<T>,
+    /// The first `k` terms.
+    pub initial: Vec<T>,
+}
+
+impl<T: Copy + PartialEq> LinearRecurrence<T> {
+    /// Finds the shortest recurrence generating `sequence` using
+    /// Berlekamp-Massey. A recurrence of order `k` is only determined by at
+    /// least `2k` terms, so the sequence should be at least twice as long as
+    /// the order expected.
+    pub fn find<F: Field<T>>(field: &F, sequence: &[T]) -> Self {
+        field.validate();
+        let zero = field.zero();
+        let mut connection = vec![field.one()];
+        let mut previous = vec![field.one()];
+        let mut previous_discrepancy = field.one();
+        let mut length = 0;
+        let mut shift = 1;
+        for n in 0..sequence.len() {
+            let discrepancy = (1..=length).fold(sequence[n], |acc, i| {
+                field.add(acc, field.mul(connection[i], sequence[n - i]))
+            });
+            if discrepancy == zero {
+                shift += 1;
+                continue;
+            }
+            let scale = field.neg(field.mul(discrepancy, field.inv(previous_discrepancy)));
+            let old_connection = connection.clone();
+            if connection.len() < previous.len() + shift {
+                connection.resize(previous.len() + shift, zero);
+            }
+            for (i, &b) in previous.iter().enumerate() {
+                connection[i + shift] = field.add(connection[i + shift], field.mul(scale, b));
+            }
+            if 2 * length <= n {
+                length = n + 1 - length;
+                previous = old_connection;
+                previous_discrepancy = discrepancy;
+                shift = 1;
+            } else {
+                shift += 1;
+            }
+        }
+        connection.resize(length + 1, zero);
+        Self {
+            coefficients: connection[1..].iter().map(|&c| field.neg(c)).collect(),
+            initial: sequence[..length].to_vec(),
+        }
+    }
+
+    pub fn order(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Computes the term at index `n` by raising the companion matrix to a
+    /// power, so `n` may be huge.
+    pub fn nth<F: Field<T>>(&self, field: &F, n: u64) -> T {
+        let k = self.order();
+        if n < k as u64 {
+            return self.initial[n as usize];
+        }
+        if k == 0 {
+            return field.zero();
+        }
+        let companion = Array2::from_shape_fn((k, k), |(i, j)| {
+            if i == 0 {
+                self.coefficients[j]
+            } else if i == j + 1 {
+                field.one()
+            } else {
+                field.zero()
+            }
+        });
+        let latest_initial = Array1::from_shape_fn(k, |i| self.initial[k - 1 - i]);
+        let transform = matrix::pow(field, &companion, n - (k as u64 - 1));
+        matrix::multiply_vector(field, &transform, &latest_initial)[0]
+    }
+}
+
+/// Finds the shortest recurrence generating `sequence` and uses it to compute
+/// the term at index `n`.
+pub fn extrapolate<T: Copy + PartialEq, F: Field<T>>(field: &F, sequence: &[T], n: u64) -> T {
+    LinearRecurrence::find(field, sequence).nth(field, n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `2^61 - 1`, large enough that most puzzle answers are exact.
+    const PRIME: u64 = 2_305_843_009_213_693_951;
+
+    #[test]
+    fn test_fibonacci() {
+        let field = Modular(PRIME);
+        let recurrence = LinearRecurrence::find(&field, &[0, 1, 1, 2, 3, 5, 8, 13]);
+        assert_eq!(
+            recurrence,
+            LinearRecurrence {
+                coefficients: vec![1, 1],
+                initial: vec![0, 1],
+            }
+        );
+        assert_eq!(recurrence.nth(&field, 85), 259_695_496_911_122_585);
+        assert_eq!(recurrence.nth(&field, 1), 1);
+    }
+
+    #[test]
+    fn test_degenerate_sequences() {
+        let field = Modular(7_i64);
+        assert_eq!(LinearRecurrence::find(&field, &[0, 0, 0]).order(), 0);
+        assert_eq!(extrapolate(&field, &[0, 0, 0], 100), 0);
+        assert_eq!(extrapolate(&field, &[3, 3, 3, 3], 100), 3);
+        assert_eq!(extrapolate(&field, &[1, 2, 4, 1, 2, 4], 100), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Modulus 15 is not prime.")]
+    fn test_composite_modulus() {
+        LinearRecurrence::find(&Modular(15_i64), &[1, 2, 4, 8, 1]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let field = Modular(1_000_000_007_i64);
+        let expected = LinearRecurrence {
+            coefficients: vec![3, 0, 999_999_999, 12345],
+            initial: vec![1, 4, 9, 16],
+        };
+        let sequence = (0..8).map(|n| expected.nth(&field, n)).collect::<Vec<_>>();
+        let found = LinearRecurrence::find(&field, &sequence);
+        assert_eq!(found, expected);
+        assert_eq!(found.nth(&field, 1 << 40), expected.nth(&field, 1 << 40));
+    }
+
+    #[test]
+    fn test_rationals() {
+        // s(n) = s(n - 1) / 2 + s(n - 2) / 3
+        let expected = LinearRecurrence {
+            coefficients: vec![Rational::<i128>::new(1, 2), Rational::new(1, 3)],
+            initial: vec![Rational::from(6), Rational::new(-1, 4)],
+        };
+        let sequence = (0..6)
+            .map(|n| expected.nth(&Ordinary, n))
+            .collect::<Vec<_>>();
+        assert_eq!(sequence[2], Rational::new(15, 8));
+        let found = LinearRecurrence::find(&Ordinary, &sequence);
+        assert_eq!(found, expected);
+        assert_eq!(found.nth(&Ordinary, 20), expected.nth(&Ordinary, 20));
+    }
+
+    #[test]
+    fn test_lanternfish() {
+        let mut counts = [0_u64; 9];
+        for age in [3, 4, 3, 1, 2] {
+            counts[age] += 1;
+        }
+        let mut totals = vec![];
+        for _ in 0..20 {
+            totals.push(counts.iter().sum::<u64>());
+            counts.rotate_left(1);
+            counts[6] += counts[8];
+        }
+        let field = Modular(PRIME);
+        assert_eq!(extrapolate(&field, &totals, 80), 5934);
+        assert_eq!(extrapolate(&field, &totals, 256), 26_984_457_539);
+    }
+}
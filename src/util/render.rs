@@ -0,0 +1,268 @@
+use crate::util::coords::Coord2;
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+
+pub type Point = Coord2<i64>;
+
+/// A rectangular region rendered to text cell by cell, for debugging. Cells
+/// whose text is wider than one character are right-aligned in columns
+/// separated by spaces.
+#[derive(Clone, Debug)]
+pub struct Rendering {
+    min: Point,
+    cells: Vec<Vec<Cell>>,
+    has_rulers: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Cell {
+    text: String,
+    width: usize,
+}
+
+impl Rendering {
+    /// Renders every point from `min` to `max` inclusive using `f`.
+    pub fn new<D: Display>(min: Point, max: Point, mut f: impl FnMut(Point) -> D) -> Self {
+        let cells = (min.0..=max.0)
+            .map(|i| {
+                (min.1..=max.1)
+                    .map(|j| {
+                        let text = f(Coord2(i, j)).to_string();
+                        Cell {
+                            width: text.chars().count(),
+                            text,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            min,
+            cells,
+            has_rulers: false,
+        }
+    }
+
+    /// Labels each row and column with its index.
+    pub fn with_rulers(mut self) -> Self {
+        self.has_rulers = true;
+        self
+    }
+
+    /// Shows the cells at the given points in reverse video.
+    pub fn highlight(self, points: impl IntoIterator<Item = Point>) -> Self {
+        self.highlight_with(points, |text| format!("\x1b[7m{}\x1b[0m", text))
+    }
+
+    /// Rewrites the text of the cells at the given points. The new text is
+    /// treated as having the same width as the old, so `f` may add terminal
+    /// escape codes or swap in a marker of equal width.
+    pub fn highlight_with(
+        mut self,
+        points: impl IntoIterator<Item = Point>,
+        mut f: impl FnMut(&str) -> String,
+    ) -> Self {
+        for point in points {
+            let i = usize::try_from(point.0 - self.min.0).ok();
+            let j = usize::try_from(point.1 - self.min.1).ok();
+            let cell = i
+                .zip(j)
+                .and_then(|(i, j)| self.cells.get_mut(i)?.get_mut(j));
+            if let Some(cell) = cell {
+                cell.text = f(&cell.text);
+            }
+        }
+        self
+    }
+
+    /// Each line of text along with its visible width.
+    fn lines(&self) -> Vec<(String, usize)> {
+        let nrows = self.cells.len();
+        let ncols = self.cells.first().map_or(0, |row| row.len());
+        let cell_width = self
+            .cells
+            .iter()
+            .flatten()
+            .map(|cell| cell.width)
+            .max()
+            .unwrap_or(1);
+        let separator = if cell_width > 1 { " " } else { "" };
+        let row_labels = (0..nrows)
+            .map(|i| (self.min.0 + i as i64).to_string())
+            .collect::<Vec<_>>();
+        let row_label_width = row_labels.iter().map(|s| s.len()).max().unwrap_or(0);
+        let prefix_width = if self.has_rulers {
+            row_label_width + 1
+        } else {
+            0
+        };
+        let line_width =
+            prefix_width + ncols * cell_width + ncols.saturating_sub(1) * separator.len();
+        let mut result = vec![];
+        if self.has_rulers {
+            let col_labels = (0..ncols)
+                .map(|j| (self.min.1 + j as i64).to_string())
+                .collect::<Vec<_>>();
+            let height = col_labels.iter().map(|s| s.len()).max().unwrap_or(0);
+            for k in 0..height {
+                let labels = col_labels
+                    .iter()
+                    .map(|label| {
+                        let c = (k + label.len())
+                            .checked_sub(height)
+                            .map_or(' ', |index| label.as_bytes()[index] as char);
+                        format!("{:>width$}", c, width = cell_width)
+                    })
+                    .collect::<Vec<_>>();
+                let line = format!("{:prefix_width$}{}", "", labels.join(separator));
+                result.push((line, line_width));
+            }
+        }
+        for (row, label) in self.cells.iter().zip(&row_labels) {
+            let mut line = if self.has_rulers {
+                format!("{:>width$} ", label, width = row_label_width)
+            } else {
+                String::new()
+            };
+            for (j, cell) in row.iter().enumerate() {
+                if j > 0 {
+                    line.push_str(separator);
+                }
+                line.extend((cell.width..cell_width).map(|_| ' '));
+                line.push_str(&cell.text);
+            }
+            result.push((line, line_width));
+        }
+        result
+    }
+}
+
+impl Display for Rendering {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lines = self.lines();
+        let text = lines
+            .into_iter()
+            .map(|(line, _)| line)
+            .collect::<Vec<_>>()
+            .join("\n");
+        f.write_str(&text)
+    }
+}
+
+/// Renders a set of points, marking those in the set with `mark` and the rest
+/// of their bounding box with `blank`.
+pub fn render_points(
+    points: impl IntoIterator<Item = Point>,
+    mark: char,
+    blank: char,
+) -> Rendering {
+    let points = points.into_iter().collect::<HashSet<_>>();
+    let (min, max) =
+        Coord2::bounding_box(points.iter().copied()).unwrap_or((Coord2(0, 0), Coord2(-1, -1)));
+    Rendering::new(
+        min,
+        max,
+        |point| {
+            if points.contains(&point) {
+                mark
+            } else {
+                blank
+            }
+        },
+    )
+}
+
+/// Places renderings next to each other, such as successive generations of a
+/// simulation, with their top lines aligned.
+pub fn side_by_side(renderings: &[Rendering]) -> String {
+    let all_lines = renderings.iter().map(|r| r.lines()).collect::<Vec<_>>();
+    let widths = all_lines
+        .iter()
+        .map(|lines| lines.iter().map(|&(_, width)| width).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    let height = all_lines.iter().map(|lines| lines.len()).max().unwrap_or(0);
+    (0..height)
+        .map(|i| {
+            // Pad every part but the last present one, so that trailing blank
+            // cells are kept but nothing is added after them.
+            let last = (0..all_lines.len())
+                .rev()
+                .find(|&k| i < all_lines[k].len())
+                .unwrap();
+            all_lines[..=last]
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(k, (lines, &width))| match lines.get(i) {
+                    Some((line, _)) if k == last => line.clone(),
+                    Some((line, line_width)) => {
+                        format!("{}{:pad$}", line, "", pad = width - line_width)
+                    }
+                    None => format!("{:width$}", ""),
+                })
+                .collect::<Vec<_>>()
+                .join("   ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let rendering = Rendering::new(Coord2(0, 0), Coord2(1, 2), |p| p.0 * 3 + p.1);
+        assert_eq!(rendering.to_string(), "012\n345");
+        let rendering = Rendering::new(Coord2(0, 0), Coord2(1, 1), |p| p.0 * 10 + p.1);
+        assert_eq!(rendering.to_string(), " 0  1\n10 11");
+    }
+
+    #[test]
+    fn test_rulers() {
+        let rendering = Rendering::new(Coord2(-1, 9), Coord2(1, 11), |_| '.').with_rulers();
+        assert_eq!(
+            rendering.to_string(),
+            ["    11", "   901", "-1 ...", " 0 ...", " 1 ..."].join("\n")
+        );
+    }
+
+    #[test]
+    fn test_highlight() {
+        let rendering = Rendering::new(Coord2(0, 0), Coord2(2, 2), |_| '.')
+            .highlight_with([Coord2(0, 0), Coord2(1, 1), Coord2(5, 5)], |_| {
+                "#".to_owned()
+            });
+        assert_eq!(rendering.to_string(), "#..\n.#.\n...");
+        let rendering =
+            Rendering::new(Coord2(0, 0), Coord2(0, 1), |_| '.').highlight([Coord2(0, 1)]);
+        assert_eq!(rendering.to_string(), ".\x1b[7m.\x1b[0m");
+    }
+
+    #[test]
+    fn test_render_points() {
+        let rendering = render_points([Coord2(1, 1), Coord2(2, 3)], '#', '.');
+        assert_eq!(rendering.to_string(), "#..\n..#");
+        assert_eq!(render_points([], '#', '.').to_string(), "");
+    }
+
+    #[test]
+    fn test_side_by_side() {
+        let a = Rendering::new(Coord2(0, 0), Coord2(1, 1), |_| 'a');
+        let b = Rendering::new(Coord2(0, 0), Coord2(2, 0), |_| 'b').highlight([Coord2(0, 0)]);
+        let c = Rendering::new(Coord2(0, 0), Coord2(0, 0), |_| 'c');
+        assert_eq!(
+            side_by_side(&[a, b, c]),
+            "aa   \x1b[7mb\x1b[0m   c\naa   b\n     b"
+        );
+        let blank = Rendering::new(
+            Coord2(0, 0),
+            Coord2(0, 2),
+            |p| if p.1 == 0 { '#' } else { ' ' },
+        );
+        let a = Rendering::new(Coord2(0, 0), Coord2(0, 0), |_| 'a');
+        assert_eq!(side_by_side(&[a.clone(), blank.clone()]), "a   #  ");
+        assert_eq!(side_by_side(&[blank, a]), "#     a");
+    }
+}
@@ -0,0 +1,267 @@
+use crate::util::coords::Coord2;
+use crate::util::grid::Grid;
+use crate::util::render::Rendering;
+use ndarray::Array2;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+pub type Point = Coord2<i64>;
+
+/// An unbounded grid which stores only the cells that have been set. Every
+/// other cell holds the default value. Coordinates are `(row, column)`, as for
+/// `Grid`.
+#[derive(Clone, Debug)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    default: T,
+    /// Bounds which contain every set cell, though after a removal they may
+    /// be larger than needed until `stale_bounds` is cleared.
+    bounds: Cell<Option<(Point, Point)>>,
+    /// Set when a cell on the edge of the bounds is removed, so that the
+    /// bounds are recomputed on the next call to `bounds` rather than after
+    /// every removal.
+    stale_bounds: Cell<bool>,
+}
+
+impl<T: Clone> SparseGrid<T> {
+    pub fn new(default: T) -> Self {
+        Self {
+            cells: HashMap::new(),
+            default,
+            bounds: Cell::new(None),
+            stale_bounds: Cell::new(false),
+        }
+    }
+
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+
+    /// Changes the value of every cell which has not been set.
+    pub fn set_default(&mut self, default: T) {
+        self.default = default;
+    }
+
+    pub fn get(&self, point: Point) -> &T {
+        self.cells.get(&point).unwrap_or(&self.default)
+    }
+
+    /// Returns a mutable reference to the cell, setting it to the default value
+    /// first if it has not been set.
+    pub fn get_mut(&mut self, point: Point) -> &mut T {
+        self.expand_bounds(point);
+        let default = &self.default;
+        self.cells.entry(point).or_insert_with(|| default.clone())
+    }
+
+    pub fn set(&mut self, point: Point, value: T) {
+        self.expand_bounds(point);
+        self.cells.insert(point, value);
+    }
+
+    /// Unsets the cell so that it holds the default value again.
+    pub fn remove(&mut self, point: Point) -> Option<T> {
+        let result = self.cells.remove(&point);
+        if let Some((min, max)) = self.bounds.get() {
+            if point.0 == min.0 || point.0 == max.0 || point.1 == min.1 || point.1 == max.1 {
+                self.stale_bounds.set(true);
+            }
+        }
+        result
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.cells.contains_key(&point)
+    }
+
+    /// The number of cells which have been set.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Iterates over the cells which have been set, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(&point, value)| (point, value))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.values()
+    }
+
+    /// The smallest and largest row and column of any cell which has been set,
+    /// as inclusive `(min, max)` corners.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        if self.stale_bounds.replace(false) {
+            let bounds = self
+                .cells
+                .keys()
+                .fold(None, |bounds, &point| Some(expand_bounds(bounds, point)));
+            self.bounds.set(bounds);
+        }
+        self.bounds.get()
+    }
+
+    /// Every point within the bounds, row by row.
+    pub fn points_in_bounds(&self) -> impl Iterator<Item = Point> {
+        let (min, max) = self.bounds().unwrap_or((Coord2(0, 0), Coord2(-1, -1)));
+        (min.0..=max.0).flat_map(move |i| (min.1..=max.1).map(move |j| Coord2(i, j)))
+    }
+
+    pub fn neighbors(&self, point: Point) -> impl Iterator<Item = Point> {
+        point.neighbors().into_iter()
+    }
+
+    pub fn orthogonal_neighbors(&self, point: Point) -> impl Iterator<Item = Point> {
+        point.orthogonal_neighbors().into_iter()
+    }
+
+    pub fn map<U: Clone>(&self, mut f: impl FnMut(&T) -> U) -> SparseGrid<U> {
+        SparseGrid {
+            cells: self
+                .cells
+                .iter()
+                .map(|(&point, value)| (point, f(value)))
+                .collect(),
+            default: f(&self.default),
+            bounds: self.bounds.clone(),
+            stale_bounds: self.stale_bounds.clone(),
+        }
+    }
+
+    /// Copies the cells within the bounds into a dense grid. Also returns the
+    /// point which corresponds to index `[0, 0]` of the grid.
+    pub fn to_grid(&self) -> (Grid<T>, Point) {
+        let (min, max) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => {
+                return (
+                    Grid(Array2::from_shape_vec((0, 0), vec![]).unwrap()),
+                    Coord2(0, 0),
+                )
+            }
+        };
+        let shape = ((max.0 - min.0 + 1) as usize, (max.1 - min.1 + 1) as usize);
+        let array = Array2::from_shape_fn(shape, |(i, j)| {
+            self.get(min + Coord2(i as i64, j as i64)).clone()
+        });
+        (Grid(array), min)
+    }
+
+    /// Renders the cells within the bounds using `f`.
+    pub fn render<D: Display>(&self, mut f: impl FnMut(&T) -> D) -> Rendering {
+        let (min, max) = self.bounds().unwrap_or((Coord2(0, 0), Coord2(-1, -1)));
+        Rendering::new(min, max, |point| f(self.get(point)))
+    }
+
+    fn expand_bounds(&mut self, point: Point) {
+        let bounds = self.bounds.get_mut();
+        *bounds = Some(expand_bounds(*bounds, point));
+    }
+}
+
+fn expand_bounds(bounds: Option<(Point, Point)>, point: Point) -> (Point, Point) {
+    match bounds {
+        Some((min, max)) => (min.component_min(point), max.component_max(point)),
+        None => (point, point),
+    }
+}
+
+impl<T: Clone + PartialEq> SparseGrid<T> {
+    /// Creates a sparse grid holding the cells of `grid` which differ from
+    /// `default`, with index `[i, j]` placed at point `(i, j)`.
+    pub fn from_grid(grid: &Grid<T>, default: T) -> Self {
+        let mut result = Self::new(default);
+        for [i, j] in grid.indices() {
+            if grid[[i, j]] != result.default {
+                result.set(Coord2(i as i64, j as i64), grid[[i, j]].clone());
+            }
+        }
+        result
+    }
+}
+
+impl<T: Clone + Display> Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(T::to_string))
+    }
+}
+
+impl<T: Clone + Default> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Clone + Default> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let mut result = Self::default();
+        for (point, value) in iter {
+            result.set(point, value);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_and_bounds() {
+        let mut grid = SparseGrid::new(0);
+        assert_eq!(grid.bounds(), None);
+        grid.set(Coord2(2, -3), 5);
+        *grid.get_mut(Coord2(-1, 4)) += 1;
+        *grid.get_mut(Coord2(-1, 4)) += 1;
+        assert_eq!(*grid.get(Coord2(2, -3)), 5);
+        assert_eq!(*grid.get(Coord2(-1, 4)), 2);
+        assert_eq!(*grid.get(Coord2(100, 100)), 0);
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid.bounds(), Some((Coord2(-1, -3), Coord2(2, 4))));
+        grid.remove(Coord2(-1, 4));
+        assert_eq!(grid.bounds(), Some((Coord2(2, -3), Coord2(2, -3))));
+        grid.set_default(7);
+        assert_eq!(*grid.get(Coord2(-1, 4)), 7);
+        grid.remove(Coord2(2, -3));
+        assert_eq!(grid.bounds(), None);
+    }
+
+    #[test]
+    fn test_many_removals() {
+        let mut grid: SparseGrid<bool> = (0..1000).map(|i| (Coord2(i, i), true)).collect();
+        for i in 0..999 {
+            grid.remove(Coord2(i, i));
+        }
+        grid.set(Coord2(2000, -5), true);
+        assert_eq!(grid.bounds(), Some((Coord2(999, -5), Coord2(2000, 999))));
+    }
+
+    #[test]
+    fn test_grid_round_trip() {
+        let dense = Grid::parse_chars("#..\n.#.\n..#\n...");
+        let sparse = SparseGrid::from_grid(&dense, '.');
+        assert_eq!(sparse.len(), 3);
+        assert_eq!(sparse.bounds(), Some((Coord2(0, 0), Coord2(2, 2))));
+        let (round_trip, origin) = sparse.to_grid();
+        assert_eq!(origin, Coord2(0, 0));
+        assert_eq!(round_trip, Grid::parse_chars("#..\n.#.\n..#"));
+    }
+
+    #[test]
+    fn test_render() {
+        let grid: SparseGrid<bool> = [(Coord2(-1, 0), true), (Coord2(1, 2), true)]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            grid.render(|&b| if b { '#' } else { '.' }).to_string(),
+            "#..\n...\n..#"
+        );
+        assert_eq!(grid.points_in_bounds().count(), 9);
+        assert_eq!(grid.neighbors(Coord2(0, 0)).count(), 8);
+    }
+}